@@ -1,3 +1,5 @@
+#![allow(dead_code)]
+
 use fromit::FromIt;
 
 fn conv(x: &String) -> Result<Vec<u8>, std::convert::Infallible> {
@@ -0,0 +1,531 @@
+use super::*;
+
+/// The options of `builder`, which can be a word or a list.
+#[derive(Default)]
+pub(crate) struct StructLevelBuilder {
+  /// Tracks the set fields in the type of the builder, so `build()` only exists
+  /// once every required field is set.
+  pub(crate) typestate: bool,
+}
+
+impl FromMeta for StructLevelBuilder {
+  fn from_word() -> darling::Result<Self> {
+    Ok(Self::default())
+  }
+
+  fn from_list(items: &[syn::NestedMeta]) -> darling::Result<Self> {
+    let mut typestate: (bool, Option<bool>) = (false, None);
+    let mut errors = darling::Error::accumulator();
+    for item in items {
+      match item {
+        syn::NestedMeta::Meta(inner) => {
+          let name = darling::util::path_to_string(inner.path());
+          match name.as_str() {
+            "typestate" => {
+              if !typestate.0 {
+                typestate = (
+                  true,
+                  errors.handle(bool::from_meta(inner).map_err(|e| e.at(&name))),
+                );
+              } else {
+                errors.push(darling::Error::duplicate_field(&name).with_span(inner));
+              }
+            }
+            other => {
+              errors.push(
+                darling::Error::unknown_field_with_alts(other, &["typestate"]).with_span(inner),
+              );
+            }
+          }
+        }
+        syn::NestedMeta::Lit(inner) => {
+          errors.push(darling::Error::unsupported_format("literal").with_span(inner));
+        }
+      }
+    }
+    errors.finish()?;
+    Ok(Self {
+      typestate: typestate.1.unwrap_or(false),
+    })
+  }
+}
+
+/// Returns the name of the field of the builder which stores the given member.
+fn builder_field(member: &syn::Member) -> syn::Ident {
+  match member {
+    syn::Member::Named(ident) => ident.clone(),
+    syn::Member::Unnamed(idx) => format_ident!("field_{}", idx.index),
+  }
+}
+
+fn builder_method(
+  style: SetterStyle,
+  fn_vis: &syn::Visibility,
+  bound: Option<&syn::Generics>,
+  field_name: &syn::Ident,
+  field_ty: &syn::Type,
+) -> proc_macro2::TokenStream {
+  match style {
+    SetterStyle::Ref => quote! {
+      #fn_vis fn #field_name #bound (&mut self, val: #field_ty) -> &mut Self {
+        self.#field_name = ::core::option::Option::Some(val);
+        self
+      }
+    },
+    SetterStyle::Move => quote! {
+      #fn_vis fn #field_name #bound (mut self, val: #field_ty) -> Self {
+        self.#field_name = ::core::option::Option::Some(val);
+        self
+      }
+    },
+    SetterStyle::Into => quote! {
+      #fn_vis fn #field_name #bound (mut self, val: impl ::core::convert::Into<#field_ty>) -> Self {
+        self.#field_name = ::core::option::Option::Some(::core::convert::Into::into(val));
+        self
+      }
+    },
+    SetterStyle::TryInto => {
+      let bound = try_into_generics(bound);
+      quote! {
+        #fn_vis fn #field_name #bound (mut self, val: impl ::core::convert::TryInto<#field_ty, Error = Error>) -> ::core::result::Result<Self, Error> {
+          self.#field_name = ::core::option::Option::Some(::core::convert::TryInto::try_into(val)?);
+          ::core::result::Result::Ok(self)
+        }
+      }
+    }
+  }
+}
+
+pub(crate) fn generate_builder(
+  opts: &StructOpts,
+  final_generics: &FinalGenerics,
+) -> syn::Result<proc_macro2::TokenStream> {
+  if opts.builder.is_none() {
+    return Ok(quote!());
+  }
+  if opts.variants.is_some() {
+    return Err(syn::Error::new_spanned(
+      &opts.name,
+      "builder is not supported for enum",
+    ));
+  }
+
+  if matches!(&opts.builder, Some(b) if b.typestate) {
+    return generate_typestate_builder(opts, final_generics);
+  }
+
+  let name = &opts.name;
+  let vis = &opts.vis;
+  let builder_name = format_ident!("{}Builder", name);
+  let error_name = format_ident!("{}BuilderError", name);
+
+  let mut fields = Vec::new();
+  let mut inits = Vec::new();
+  let mut methods = Vec::new();
+  let mut checks = Vec::new();
+  let mut values = Vec::new();
+  for (member, m) in opts.members()? {
+    let field_name = builder_field(&member);
+    let field_ty = m.ty();
+    let setter = m.setter();
+    let fn_vis = setter.vis.as_ref().unwrap_or_else(|| {
+      opts
+        .setters
+        .vis_all
+        .as_ref()
+        .unwrap_or_else(|| m.vis().unwrap_or(&opts.vis))
+    });
+
+    let cfgs = m.cfgs();
+    fields.push(quote! {
+      #(#cfgs)* #field_name: ::core::option::Option<#field_ty>,
+    });
+    inits.push(quote! {
+      #(#cfgs)* #field_name: ::core::option::Option::None,
+    });
+    let method = builder_method(
+      setter.style.unwrap_or(opts.setters.style),
+      fn_vis,
+      setter.bound.bound.as_ref(),
+      &field_name,
+      field_ty,
+    );
+    methods.push(quote!(#(#cfgs)* #method));
+
+    match m {
+      Member::Extra(ExtraField {
+        default: Some(default),
+        ..
+      }) => values.push(quote! {
+        #member: self.#field_name.unwrap_or_else(#default),
+      }),
+      _ => {
+        let missing = member_name(&member);
+        checks.push(quote! {
+          #(#cfgs)*
+          if self.#field_name.is_none() {
+            missing.push(#missing);
+          }
+        });
+        values.push(quote! {
+          #(#cfgs)* #member: self.#field_name.unwrap(),
+        });
+      }
+    }
+  }
+
+  let impl_generics = &final_generics.impl_generics;
+  let ty_generics = &final_generics.ty_generics;
+  let where_clause = &final_generics.where_clause;
+  let struct_generics = &final_generics.final_struct_generics;
+  let builder_doc = format!("A builder for [`{}`].", name);
+  let error_doc = format!(
+    "The error returned when [`{}`] misses fields.",
+    builder_name
+  );
+  let error_msg = format!("`{}` is missing fields: ", name);
+  let allow = opts.allow_renamed();
+  Ok(quote! {
+    #[doc = #builder_doc]
+    #allow
+    #vis struct #builder_name #struct_generics #where_clause {
+      #(#fields)*
+    }
+
+    impl #impl_generics ::core::default::Default for #builder_name #ty_generics #where_clause {
+      fn default() -> Self {
+        Self::new()
+      }
+    }
+
+    #allow
+    impl #impl_generics #builder_name #ty_generics #where_clause {
+      /// Creates a builder with no fields set.
+      #vis fn new() -> Self {
+        Self {
+          #(#inits)*
+        }
+      }
+
+      #(#methods)*
+
+      /// Builds the value, fails if any required field is not set.
+      #vis fn build(self) -> ::core::result::Result<#name #ty_generics, #error_name> {
+        let mut missing = ::std::vec::Vec::new();
+        #(#checks)*
+        if !missing.is_empty() {
+          return ::core::result::Result::Err(#error_name { missing });
+        }
+
+        ::core::result::Result::Ok(#name {
+          #(#values)*
+        })
+      }
+    }
+
+    impl #impl_generics #name #ty_generics #where_clause {
+      /// Returns a builder of this struct.
+      #vis fn builder() -> #builder_name #ty_generics {
+        #builder_name::new()
+      }
+    }
+
+    #[doc = #error_doc]
+    #[derive(::core::fmt::Debug, ::core::clone::Clone, ::core::cmp::PartialEq, ::core::cmp::Eq)]
+    #vis struct #error_name {
+      missing: ::std::vec::Vec<&'static str>,
+    }
+
+    impl #error_name {
+      /// Returns the names of the missing fields.
+      #vis fn missing_fields(&self) -> &[&'static str] {
+        &self.missing
+      }
+    }
+
+    impl ::core::fmt::Display for #error_name {
+      fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+        f.write_str(#error_msg)?;
+        for (idx, field) in self.missing.iter().enumerate() {
+          if idx > 0 {
+            f.write_str(", ")?;
+          }
+          ::core::write!(f, "`{}`", field)?;
+        }
+        ::core::result::Result::Ok(())
+      }
+    }
+
+    impl ::std::error::Error for #error_name {}
+  })
+}
+
+/// Returns the generic arguments of the given generics, e.g. `'a, T, N` for
+/// `<'a, T: Clone, const N: usize>`.
+fn generic_args(generics: &syn::Generics) -> Vec<proc_macro2::TokenStream> {
+  generics
+    .params
+    .iter()
+    .map(|param| match param {
+      syn::GenericParam::Type(t) => t.ident.to_token_stream(),
+      syn::GenericParam::Lifetime(l) => l.lifetime.to_token_stream(),
+      syn::GenericParam::Const(c) => c.ident.to_token_stream(),
+    })
+    .collect()
+}
+
+/// Returns the generic parameters without defaults, which can be used by an `impl`.
+fn impl_params(generics: &syn::Generics) -> Vec<syn::GenericParam> {
+  generics
+    .params
+    .iter()
+    .cloned()
+    .map(|param| match param {
+      syn::GenericParam::Type(mut t) => {
+        t.eq_token = None;
+        t.default = None;
+        syn::GenericParam::Type(t)
+      }
+      syn::GenericParam::Const(mut c) => {
+        c.eq_token = None;
+        c.default = None;
+        syn::GenericParam::Const(c)
+      }
+      param => param,
+    })
+    .collect()
+}
+
+/// A required field of the typestate builder.
+struct Required {
+  field_name: syn::Ident,
+  state: syn::Ident,
+  ty: syn::Type,
+}
+
+fn generate_typestate_builder(
+  opts: &StructOpts,
+  final_generics: &FinalGenerics,
+) -> syn::Result<proc_macro2::TokenStream> {
+  let name = &opts.name;
+  let vis = &opts.vis;
+  let builder_name = format_ident!("{}Builder", name);
+  let generics = &final_generics.generics;
+  let params = impl_params(generics);
+  let args = generic_args(generics);
+  let ty_generics = &final_generics.ty_generics;
+  let where_clause = &final_generics.where_clause;
+
+  let mut members = Vec::new();
+  let mut required = Vec::new();
+  let mut optional_fields = Vec::new();
+  let mut optional_methods = Vec::new();
+  let all = opts.members()?;
+  if let Some((member, _)) = all.iter().find(|(_, m)| !m.cfgs().is_empty()) {
+    return Err(syn::Error::new_spanned(
+      member,
+      "cfg fields are not supported by builder(typestate)",
+    ));
+  }
+  for (member, m) in &all {
+    let field_name = builder_field(member);
+    let setter = m.setter();
+    let fn_vis = setter.vis.as_ref().unwrap_or_else(|| {
+      opts
+        .setters
+        .vis_all
+        .as_ref()
+        .unwrap_or_else(|| m.vis().unwrap_or(&opts.vis))
+    });
+    let field_ty = m.ty();
+    match m {
+      Member::Extra(ExtraField {
+        default: Some(default),
+        ..
+      }) => {
+        optional_fields.push(quote! {
+          #field_name: ::core::option::Option<#field_ty>,
+        });
+        optional_methods.push(builder_method(
+          setter.style.unwrap_or(opts.setters.style),
+          fn_vis,
+          setter.bound.bound.as_ref(),
+          &field_name,
+          field_ty,
+        ));
+        members.push((member, quote!(self.#field_name.unwrap_or_else(#default))));
+      }
+      _ => {
+        members.push((member, quote!(self.#field_name.0)));
+        required.push((
+          Required {
+            state: format_ident!("__State{}", required.len()),
+            field_name,
+            ty: field_ty.clone(),
+          },
+          setter,
+          fn_vis,
+        ));
+      }
+    }
+  }
+
+  let optional_names = all
+    .iter()
+    .filter_map(|(member, m)| match m {
+      Member::Extra(ExtraField {
+        default: Some(_), ..
+      }) => Some(builder_field(member)),
+      _ => None,
+    })
+    .collect::<Vec<_>>();
+  let states = required
+    .iter()
+    .map(|(r, _, _)| &r.state)
+    .collect::<Vec<_>>();
+  let state_fields = required.iter().map(|(r, _, _)| {
+    let field_name = &r.field_name;
+    let state = &r.state;
+    quote!(#field_name: #state,)
+  });
+  let unset = required.iter().map(|_| quote!(())).collect::<Vec<_>>();
+  let set = required
+    .iter()
+    .map(|(r, _, _)| {
+      let ty = &r.ty;
+      quote!((#ty,))
+    })
+    .collect::<Vec<_>>();
+
+  let mut required_methods = Vec::new();
+  for (idx, (r, setter, fn_vis)) in required.iter().enumerate() {
+    let field_name = &r.field_name;
+    let field_ty = &r.ty;
+    let free = states
+      .iter()
+      .enumerate()
+      .filter(|(i, _)| *i != idx)
+      .map(|(_, s)| s);
+    let before = states
+      .iter()
+      .enumerate()
+      .map(|(i, s)| if i == idx { quote!(()) } else { quote!(#s) });
+    let after = states
+      .iter()
+      .enumerate()
+      .map(|(i, s)| {
+        if i == idx {
+          quote!((#field_ty,))
+        } else {
+          quote!(#s)
+        }
+      })
+      .collect::<Vec<_>>();
+    let moved = required.iter().enumerate().map(|(i, (r, _, _))| {
+      let f = &r.field_name;
+      if i == idx {
+        quote!(#f: (val,),)
+      } else {
+        quote!(#f: self.#f,)
+      }
+    });
+    let next = quote! {
+      #builder_name {
+        #(#moved)*
+        #(#optional_names: self.#optional_names,)*
+        __marker: ::core::marker::PhantomData,
+      }
+    };
+    let next_ty = quote!(#builder_name<#(#args,)* #(#after,)*>);
+    let bound = setter.bound.bound.as_ref();
+    // a required field changes the type of the builder, so the `ref` style is the same as `move`
+    let method = match setter.style.unwrap_or(opts.setters.style) {
+      SetterStyle::Ref | SetterStyle::Move => quote! {
+        #fn_vis fn #field_name #bound (self, val: #field_ty) -> #next_ty {
+          #next
+        }
+      },
+      SetterStyle::Into => quote! {
+        #fn_vis fn #field_name #bound (self, val: impl ::core::convert::Into<#field_ty>) -> #next_ty {
+          let val = ::core::convert::Into::into(val);
+          #next
+        }
+      },
+      SetterStyle::TryInto => {
+        let bound = try_into_generics(bound);
+        quote! {
+          #fn_vis fn #field_name #bound (self, val: impl ::core::convert::TryInto<#field_ty, Error = Error>) -> ::core::result::Result<#next_ty, Error> {
+            let val = ::core::convert::TryInto::try_into(val)?;
+            ::core::result::Result::Ok(#next)
+          }
+        }
+      }
+    };
+    let allow = opts.allow_renamed();
+    required_methods.push(quote! {
+      #allow
+      impl<#(#params,)* #(#free,)*> #builder_name<#(#args,)* #(#before,)*> #where_clause {
+        #method
+      }
+    });
+  }
+
+  let values = members
+    .iter()
+    .map(|(member, value)| quote!(#member: #value,));
+  let required_names = required.iter().map(|(r, _, _)| &r.field_name);
+  let builder_doc = format!(
+    "A builder for [`{}`], `build()` is available once every required field is set.",
+    name
+  );
+  let allow = opts.allow_renamed();
+  Ok(quote! {
+    #[doc = #builder_doc]
+    #allow
+    #vis struct #builder_name<#(#params,)* #(#states,)*> #where_clause {
+      #(#state_fields)*
+      #(#optional_fields)*
+      __marker: ::core::marker::PhantomData<fn() -> #name #ty_generics>,
+    }
+
+    impl<#(#params,)*> ::core::default::Default for #builder_name<#(#args,)* #(#unset,)*> #where_clause {
+      fn default() -> Self {
+        Self::new()
+      }
+    }
+
+    impl<#(#params,)*> #builder_name<#(#args,)* #(#unset,)*> #where_clause {
+      /// Creates a builder with no fields set.
+      #vis fn new() -> Self {
+        Self {
+          #(#required_names: (),)*
+          #(#optional_names: ::core::option::Option::None,)*
+          __marker: ::core::marker::PhantomData,
+        }
+      }
+    }
+
+    #allow
+    impl<#(#params,)* #(#states,)*> #builder_name<#(#args,)* #(#states,)*> #where_clause {
+      #(#optional_methods)*
+    }
+
+    #(#required_methods)*
+
+    impl<#(#params,)*> #builder_name<#(#args,)* #(#set,)*> #where_clause {
+      /// Builds the value.
+      #vis fn build(self) -> #name #ty_generics {
+        #name {
+          #(#values)*
+        }
+      }
+    }
+
+    impl<#(#params,)*> #name #ty_generics #where_clause {
+      /// Returns a builder of this struct.
+      #vis fn builder() -> #builder_name<#(#args,)* #(#unset,)*> {
+        #builder_name::new()
+      }
+    }
+  })
+}
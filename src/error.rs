@@ -0,0 +1,352 @@
+use super::*;
+
+/// The `error` of `try_from`/`try_into`.
+#[derive(Clone)]
+pub(crate) enum TryError {
+  /// Generates an error enum with one variant per fallible field.
+  Auto,
+  Type(Box<syn::Type>),
+}
+
+impl FromMeta for TryError {
+  fn from_string(value: &str) -> darling::Result<Self> {
+    match value {
+      "auto" => Ok(Self::Auto),
+      value => syn::Type::from_string(value).map(|ty| Self::Type(Box::new(ty))),
+    }
+  }
+}
+
+/// How the error of a fallible field conversion is returned.
+pub(crate) enum ErrorReport {
+  /// Converted into the error of the conversion by `Into` and returned early.
+  Propagate,
+  /// Converted into the default boxed error, the index or the key of a failed
+  /// element is reported.
+  Boxed,
+  /// Boxed and wrapped by the given variant of a generated error, the index or
+  /// the key of a failed element is reported.
+  Variant(syn::Path),
+  /// Like `Variant`, but the error is pushed to the collected errors, the
+  /// conversion results in an `Option`.
+  Collect(syn::Path),
+}
+
+impl ErrorReport {
+  /// Returns whether the index or the key of a failed element is reported.
+  pub(crate) fn locates(&self) -> bool {
+    !matches!(self, Self::Propagate)
+  }
+
+  /// Returns the given `Result` expression followed by the early return of its error,
+  /// which is converted by `Into` so that `converter(infer_bounds)` can bound it.
+  pub(crate) fn raise(&self, result: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    match self {
+      Self::Variant(variant) => quote! {
+        #result.map_err(|e| #variant(::core::convert::Into::into(e)))?
+      },
+      Self::Collect(variant) => quote! {
+        match #result {
+          ::core::result::Result::Ok(v) => ::core::option::Option::Some(v),
+          ::core::result::Result::Err(e) => {
+            __fromit_errors.push(#variant(::core::convert::Into::into(e)));
+            ::core::option::Option::None
+          }
+        }
+      },
+      _ => quote! {
+        match #result {
+          ::core::result::Result::Ok(v) => v,
+          ::core::result::Result::Err(e) => {
+            return ::core::result::Result::Err(::core::convert::Into::into(e))
+          }
+        }
+      },
+    }
+  }
+}
+
+/// Returns the name of the generated error of converting `src` to `dst`.
+pub(crate) fn error_name(dst: &syn::Ident, src: &syn::Ident) -> syn::Ident {
+  format_ident!("{}From{}Error", dst, src)
+}
+
+/// Returns the name of the generated list of errors of converting `src` to `dst`.
+pub(crate) fn errors_name(dst: &syn::Ident, src: &syn::Ident) -> syn::Ident {
+  format_ident!("{}From{}Errors", dst, src)
+}
+
+/// Returns the name of the variant of a generated error for the given field key,
+/// e.g. `foo_bar` to `FooBar`, `0` to `Field0`.
+pub(crate) fn error_variant(key: &str) -> syn::Ident {
+  let key = key.trim_start_matches("r#");
+  if key.starts_with(|c: char| c.is_ascii_digit()) {
+    return format_ident!("Field{}", key);
+  }
+  let name = key
+    .split('_')
+    .filter(|part| !part.is_empty())
+    .map(|part| {
+      let mut chars = part.chars();
+      match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect::<String>(),
+        None => String::new(),
+      }
+    })
+    .collect::<String>();
+  format_ident!("{}", name)
+}
+
+/// A fallible field of a generated error.
+struct ErrorField {
+  variant: syn::Ident,
+  /// The name used in the message, e.g. `bar` or `Created.bar`.
+  name: String,
+}
+
+/// The errors of a fallible conversion.
+pub(crate) struct Errors {
+  error: Option<TryError>,
+  /// The name of the generated error, when `error = "auto"` or `collect_errors`.
+  auto: Option<syn::Ident>,
+  /// The name of the generated list of errors, when `collect_errors`.
+  collect: Option<syn::Ident>,
+  fields: Vec<ErrorField>,
+  unsupported: bool,
+}
+
+impl Errors {
+  /// Creates the errors of converting `src` to `dst`.
+  pub(crate) fn new(try_: &Try, dst: &syn::Ident, src: &syn::Ident) -> syn::Result<Self> {
+    if try_.collect_errors && matches!(try_.error, Some(TryError::Type(_))) {
+      return Err(syn::Error::new_spanned(
+        dst,
+        "collect_errors cannot be used with an error type, use `error = \"auto\"` or no error",
+      ));
+    }
+    Ok(Self {
+      auto: (try_.collect_errors || matches!(try_.error, Some(TryError::Auto)))
+        .then(|| error_name(dst, src)),
+      collect: try_.collect_errors.then(|| errors_name(dst, src)),
+      error: try_.error.clone(),
+      fields: Vec::new(),
+      unsupported: false,
+    })
+  }
+
+  /// Returns whether all the errors are collected.
+  pub(crate) fn collects(&self) -> bool {
+    self.collect.is_some()
+  }
+
+  /// Binds the given conversion of a field to a new local in `stmts` when the errors
+  /// are collected, returns the converted value. The local has the `cfgs` of the field.
+  pub(crate) fn bind(
+    &self,
+    stmts: &mut Vec<proc_macro2::TokenStream>,
+    cfgs: &[syn::Attribute],
+    converted: proc_macro2::TokenStream,
+  ) -> proc_macro2::TokenStream {
+    if self.collect.is_none() {
+      return converted;
+    }
+    let local = format_ident!("__fromit_{}", stmts.len());
+    stmts.push(quote!(#(#cfgs)* let #local = #converted;));
+    quote!(::core::option::Option::unwrap(#local))
+  }
+
+  /// Returns the construction `value` of the target preceded by the statements of
+  /// [`Errors::bind`], which returns the collected errors if any.
+  pub(crate) fn finish(
+    &self,
+    stmts: Vec<proc_macro2::TokenStream>,
+    value: proc_macro2::TokenStream,
+  ) -> proc_macro2::TokenStream {
+    match &self.collect {
+      Some(collect) if !stmts.is_empty() => quote! {
+        {
+          let mut __fromit_errors = ::std::vec::Vec::new();
+          #(#stmts)*
+          if !__fromit_errors.is_empty() {
+            return ::core::result::Result::Err(#collect { errors: __fromit_errors });
+          }
+          #value
+        }
+      },
+      _ => value,
+    }
+  }
+
+  /// Returns the report of the field with the given name, whose variant in the
+  /// generated error is `variant`.
+  pub(crate) fn report(&mut self, variant: syn::Ident, name: String) -> ErrorReport {
+    match (&self.auto, &self.error) {
+      (Some(auto), _) => {
+        let path = syn::parse_quote!(#auto::#variant);
+        self.fields.push(ErrorField { variant, name });
+        if self.collect.is_some() {
+          ErrorReport::Collect(path)
+        } else {
+          ErrorReport::Variant(path)
+        }
+      }
+      (None, None) => ErrorReport::Boxed,
+      (None, Some(_)) => ErrorReport::Propagate,
+    }
+  }
+
+  /// Returns the error for a variant which is not supported, `msg` is a `&'static str`.
+  pub(crate) fn unsupported(&mut self, msg: &str) -> proc_macro2::TokenStream {
+    self.unsupported = true;
+    match (&self.auto, &self.collect) {
+      (Some(auto), Some(collect)) => quote! {
+        #collect { errors: ::std::vec![#auto::UnsupportedVariant(#msg)] }
+      },
+      (Some(auto), None) => quote!(#auto::UnsupportedVariant(#msg)),
+      (None, _) => quote!(::core::convert::From::from(#msg)),
+    }
+  }
+
+  /// Returns the `Error` of the `TryFrom` implementation.
+  pub(crate) fn error_type(&self) -> proc_macro2::TokenStream {
+    match (&self.collect, &self.auto, &self.error) {
+      (Some(collect), _, _) => quote!(#collect),
+      (None, Some(auto), _) => quote!(#auto),
+      (None, None, Some(TryError::Type(ty))) => quote!(#ty),
+      _ => quote!(
+        ::std::boxed::Box<
+          dyn ::std::error::Error + ::core::marker::Send + ::core::marker::Sync + 'static,
+        >
+      ),
+    }
+  }
+
+  /// Returns the type which the error of a fallible field is converted into, which
+  /// is boxed when the error is generated.
+  pub(crate) fn field_error_type(&self) -> proc_macro2::TokenStream {
+    match &self.auto {
+      Some(_) => quote!(
+        ::std::boxed::Box<
+          dyn ::std::error::Error + ::core::marker::Send + ::core::marker::Sync + 'static,
+        >
+      ),
+      None => self.error_type(),
+    }
+  }
+
+  /// Generates the error enum of the conversion from `src` to `dst`, if `error = "auto"`.
+  pub(crate) fn generate(
+    &self,
+    vis: &syn::Visibility,
+    src: &syn::Ident,
+    dst: &syn::Ident,
+  ) -> proc_macro2::TokenStream {
+    let name = match &self.auto {
+      Some(name) => name,
+      None => return quote!(),
+    };
+    let doc = format!(
+      "The error of converting [`{}`] to [`{}`], which names the failed field.",
+      src, dst
+    );
+    let variants = self.fields.iter().map(|f| &f.variant).collect::<Vec<_>>();
+    let names = self.fields.iter().map(|f| &f.name).collect::<Vec<_>>();
+    let variant_docs = names
+      .iter()
+      .map(|name| format!("Fails to convert `{}`.", name));
+    let unsupported = self.unsupported;
+    let unsupported_variant = unsupported.then(|| {
+      quote! {
+        /// The variant is not supported.
+        UnsupportedVariant(&'static str),
+      }
+    });
+    let unsupported_field =
+      unsupported.then(|| quote!(Self::UnsupportedVariant(_) => ::core::option::Option::None,));
+    let unsupported_display = unsupported
+      .then(|| quote!(Self::UnsupportedVariant(msg) => ::core::fmt::Formatter::write_str(f, msg),));
+    let unsupported_source =
+      unsupported.then(|| quote!(Self::UnsupportedVariant(_) => ::core::option::Option::None,));
+    let collect = self.collect.as_ref().map(|collect| {
+      let doc = format!(
+        "All the errors of converting [`{}`] to [`{}`], every error names its field.",
+        src, dst
+      );
+      quote! {
+        #[doc = #doc]
+        #[derive(Debug)]
+        #vis struct #collect {
+          errors: ::std::vec::Vec<#name>,
+        }
+
+        impl #collect {
+          /// Returns the errors, in the order of the fields.
+          #vis fn errors(&self) -> &[#name] {
+            &self.errors
+          }
+
+          /// Returns the errors, in the order of the fields.
+          #vis fn into_errors(self) -> ::std::vec::Vec<#name> {
+            self.errors
+          }
+        }
+
+        impl ::core::fmt::Display for #collect {
+          fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+            for (idx, e) in self.errors.iter().enumerate() {
+              if idx > 0 {
+                ::core::fmt::Formatter::write_str(f, "; ")?;
+              }
+              ::core::fmt::Display::fmt(e, f)?;
+            }
+            ::core::result::Result::Ok(())
+          }
+        }
+
+        impl ::std::error::Error for #collect {}
+      }
+    });
+
+    quote! {
+      #collect
+
+      #[doc = #doc]
+      #[derive(Debug)]
+      #vis enum #name {
+        #(
+          #[doc = #variant_docs]
+          #variants(::std::boxed::Box<dyn ::std::error::Error + ::core::marker::Send + ::core::marker::Sync + 'static>),
+        )*
+        #unsupported_variant
+      }
+
+      impl #name {
+        /// Returns the name of the field which fails to convert.
+        #vis fn field(&self) -> ::core::option::Option<&'static str> {
+          match *self {
+            #(Self::#variants(_) => ::core::option::Option::Some(#names),)*
+            #unsupported_field
+          }
+        }
+      }
+
+      impl ::core::fmt::Display for #name {
+        fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+          match *self {
+            #(Self::#variants(ref e) => ::core::write!(f, "{}: {}", #names, e),)*
+            #unsupported_display
+          }
+        }
+      }
+
+      impl ::std::error::Error for #name {
+        fn source(&self) -> ::core::option::Option<&(dyn ::std::error::Error + 'static)> {
+          match *self {
+            #(Self::#variants(ref e) => ::core::option::Option::Some(&**e),)*
+            #unsupported_source
+          }
+        }
+      }
+    }
+  }
+}
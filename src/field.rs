@@ -21,6 +21,40 @@ pub(crate) struct FieldConverter {
   pub(crate) style: Option<Style>,
   #[darling(rename = "fn")]
   pub(crate) func: Option<syn::Path>,
+  /// Converts the elements of a container one by one.
+  #[darling(default)]
+  pub(crate) each: bool,
+  /// Computes the value from the whole struct `s` on the other side.
+  pub(crate) expr: Option<syn::Expr>,
+}
+
+/// The containers whose elements can be converted one by one.
+enum Container {
+  Option,
+  Seq,
+  Map,
+  Box,
+  Array,
+}
+
+impl Container {
+  fn from_type(ty: &syn::Type) -> Option<Self> {
+    match ty {
+      syn::Type::Array(_) => Some(Self::Array),
+      syn::Type::Group(g) => Self::from_type(&g.elem),
+      syn::Type::Paren(p) => Self::from_type(&p.elem),
+      syn::Type::Path(p) if p.qself.is_none() => {
+        match p.path.segments.last()?.ident.to_string().as_str() {
+          "Option" => Some(Self::Option),
+          "Vec" | "VecDeque" => Some(Self::Seq),
+          "BTreeMap" | "HashMap" => Some(Self::Map),
+          "Box" => Some(Self::Box),
+          _ => None,
+        }
+      }
+      _ => None,
+    }
+  }
 }
 
 pub(crate) struct FieldLevelSkip {
@@ -54,6 +88,40 @@ impl darling::FromMeta for FieldLevelSkip {
   }
 }
 
+/// Where an extra field is placed among the fields inherited from the source.
+pub(crate) enum Placement {
+  Position(usize),
+  Before(syn::Ident),
+  After(syn::Ident),
+}
+
+/// How an extra field is computed from the source, `from = "path::fn"` receives a
+/// reference to the whole source, `from(expr = "...")` is evaluated with the source `s`.
+pub(crate) enum ExtraFrom {
+  Fn(syn::Path),
+  Expr(syn::Expr),
+}
+
+impl FromMeta for ExtraFrom {
+  fn from_string(value: &str) -> darling::Result<Self> {
+    syn::Path::from_string(value).map(Self::Fn)
+  }
+
+  fn from_list(items: &[syn::NestedMeta]) -> darling::Result<Self> {
+    match FieldConverter::from_list(items)? {
+      FieldConverter {
+        expr: Some(expr),
+        style: None,
+        func: None,
+        each: false,
+      } => Ok(Self::Expr(expr)),
+      _ => Err(darling::Error::custom(
+        "extra field only supports `from = \"path::fn\"` or `from(expr = \"...\")`",
+      )),
+    }
+  }
+}
+
 pub(crate) struct ExtraField {
   pub(crate) name: Option<syn::Ident>,
   pub(crate) src_ty: syn::Type,
@@ -62,24 +130,75 @@ pub(crate) struct ExtraField {
   pub(crate) getter: FieldLevelGetter,
   pub(crate) setter: FieldLevelSetter,
   pub(crate) default: Option<syn::Path>,
+  pub(crate) from: Option<ExtraFrom>,
+  /// Called with the converted source and the value, when converting back.
+  pub(crate) into: Option<syn::Path>,
+  pub(crate) placement: Option<Placement>,
   pub(crate) attributes: Attributes,
   pub(crate) named: bool,
 }
 
+impl ExtraField {
+  /// Returns the value of the extra field when converting from the source `s` of
+  /// the given style, which is computed by `from` or the default. The computation
+  /// is fallible when `report` is given, and `fn` also receives the `ctx` in scope
+  /// when `context` is set.
+  pub(crate) fn convert_from(
+    &self,
+    report: Option<&ErrorReport>,
+    style: Style,
+    context: bool,
+  ) -> proc_macro2::TokenStream {
+    let converted = match &self.from {
+      Some(ExtraFrom::Fn(f)) => {
+        let src = match style {
+          Style::Ref => quote!(s),
+          Style::Move => quote!(&s),
+        };
+        if context {
+          quote!(#f(#src, &mut *ctx))
+        } else {
+          quote!(#f(#src))
+        }
+      }
+      Some(ExtraFrom::Expr(expr)) => quote!((#expr)),
+      None => {
+        return match &self.default {
+          Some(default) => quote!(#default()),
+          None => quote!(::core::default::Default::default()),
+        }
+      }
+    };
+    match report {
+      Some(report) => report.raise(converted),
+      None => converted,
+    }
+  }
+}
+
 pub(crate) struct Field {
   pub(crate) src_ty: syn::Type,
   pub(crate) src_vis: syn::Visibility,
   pub(crate) skip: Option<FieldLevelSkip>,
   pub(crate) vis: Option<syn::Visibility>,
   pub(crate) typ: Option<syn::Type>,
+  /// The field is converted by the `From`/`TryFrom` of its own target.
+  pub(crate) nested: bool,
   pub(crate) rename: Option<syn::Ident>,
   pub(crate) parent: Option<syn::Ident>,
   pub(crate) getter: FieldLevelGetter,
   pub(crate) setter: FieldLevelSetter,
   pub(crate) from: Option<FieldConverter>,
   pub(crate) into: Option<FieldConverter>,
+  pub(crate) validate: Option<Validate>,
   pub(crate) attributes: Attributes,
   pub(crate) named: bool,
+  /// The field does not have a `fromit` attribute for the target.
+  pub(crate) implicit: bool,
+  /// The doc comments of the source field.
+  pub(crate) docs: Vec<syn::Attribute>,
+  /// The `#[cfg(...)]` attributes of the source field.
+  pub(crate) cfgs: Vec<syn::Attribute>,
 }
 
 impl Field {
@@ -94,6 +213,7 @@ impl Field {
 
     if other.typ.is_some() {
       self.typ = other.typ;
+      self.nested = other.nested;
     }
 
     if other.rename.is_some() {
@@ -112,6 +232,623 @@ impl Field {
       self.into = other.into;
     }
 
+    if other.validate.is_some() {
+      self.validate = other.validate;
+    }
+
     self.attributes.attrs.extend(other.attributes.attrs);
   }
 }
+
+impl Field {
+  /// Returns the type of the field in the target.
+  pub(crate) fn target_ty(&self) -> &syn::Type {
+    self.typ.as_ref().unwrap_or(&self.src_ty)
+  }
+
+  /// Returns the conversion of the given value from the source to the target,
+  /// the conversion is fallible when `report` is given, and `fn` also receives
+  /// the `ctx` in scope when `context` is set, see [`convert_field`].
+  pub(crate) fn convert_from(
+    &self,
+    report: Option<&ErrorReport>,
+    style: Style,
+    context: bool,
+    value: proc_macro2::TokenStream,
+  ) -> syn::Result<proc_macro2::TokenStream> {
+    let converted = match &self.from {
+      Some(converter) if converter.each => convert_each(
+        converter,
+        &self.src_ty,
+        self.target_ty(),
+        report,
+        style,
+        context,
+        value,
+      )?,
+      converter => convert_field(
+        converter.as_ref(),
+        self.nested,
+        report.is_some(),
+        context,
+        value,
+      ),
+    };
+    Ok(match report {
+      Some(report) => report.raise(converted),
+      None => converted,
+    })
+  }
+
+  /// Returns the conversion of the given value from the target back to the source,
+  /// the conversion is fallible when `report` is given, see [`convert_field`].
+  pub(crate) fn convert_into(
+    &self,
+    report: Option<&ErrorReport>,
+    style: Style,
+    value: proc_macro2::TokenStream,
+  ) -> syn::Result<proc_macro2::TokenStream> {
+    let converted = match &self.into {
+      Some(converter) if converter.each => convert_each(
+        converter,
+        self.target_ty(),
+        &self.src_ty,
+        report,
+        style,
+        false,
+        value,
+      )?,
+      converter => convert_field(
+        converter.as_ref(),
+        self.nested,
+        report.is_some(),
+        false,
+        value,
+      ),
+    };
+    Ok(match report {
+      Some(report) => report.raise(converted),
+      None => converted,
+    })
+  }
+
+  /// Creates a field which is inherited as-is by the target.
+  pub(crate) fn new(field: &syn::Field, parent: &str) -> Self {
+    Self {
+      src_ty: field.ty.clone(),
+      src_vis: field.vis.clone(),
+      skip: None,
+      vis: None,
+      typ: None,
+      nested: false,
+      rename: None,
+      parent: Some(format_ident!("{}", parent)),
+      getter: Default::default(),
+      setter: Default::default(),
+      from: None,
+      into: None,
+      validate: None,
+      attributes: Default::default(),
+      named: field.ident.is_some(),
+      implicit: true,
+      docs: Vec::new(),
+      cfgs: Vec::new(),
+    }
+  }
+
+  /// Parses a `#[fromit(...)]` attribute on the given field.
+  ///
+  /// Returns `None` if the attribute is empty.
+  pub(crate) fn from_attr(
+    field: &syn::Field,
+    attr: &syn::Attribute,
+  ) -> darling::Result<Option<Self>> {
+    let mut skip: (bool, Option<FieldLevelSkip>) = (false, None);
+    let mut typ: (bool, Option<syn::Type>) = (false, None);
+    let mut nested: (bool, Option<syn::Type>) = (false, None);
+    let mut vis: (bool, Option<syn::Visibility>) = (false, None);
+    let mut rename: (bool, Option<syn::Ident>) = (false, None);
+    let mut parent: (bool, Option<syn::Ident>) = (false, None);
+    let mut from: (bool, Option<FieldConverter>) = (false, None);
+    let mut into: (bool, Option<FieldConverter>) = (false, None);
+    let mut validate: (bool, Option<Validate>) = (false, None);
+    let mut attributes: (bool, Option<Attributes>) = (false, None);
+    let mut getter: (bool, Option<FieldLevelGetter>) = (false, None);
+    let mut setter: (bool, Option<FieldLevelSetter>) = (false, None);
+    let data = ::darling::util::parse_attribute_to_meta_list(attr)?;
+    if data.nested.is_empty() {
+      return Ok(None);
+    }
+    for item in data.nested.iter() {
+      match *item {
+        syn::NestedMeta::Meta(ref inner) => {
+          let name = ::darling::util::path_to_string(inner.path());
+          match name.as_str() {
+            "default" => {
+              return Err(
+                ::darling::Error::custom("default is only supported for extra fields")
+                  .with_span(inner),
+              )
+            }
+            "skip" => crate::parser::Parser::parse(&name, inner, &mut skip)?,
+            "type" => crate::parser::Parser::parse(&name, inner, &mut typ)?,
+            "nested" => crate::parser::Parser::parse(&name, inner, &mut nested)?,
+            "rename" => crate::parser::Parser::parse(&name, inner, &mut rename)?,
+            "parent" => crate::parser::Parser::parse(&name, inner, &mut parent)?,
+            "from" => crate::parser::Parser::parse(&name, inner, &mut from)?,
+            "into" => crate::parser::Parser::parse(&name, inner, &mut into)?,
+            "validate" => crate::parser::Parser::parse(&name, inner, &mut validate)?,
+            "getter" => crate::parser::Parser::parse(&name, inner, &mut getter)?,
+            "setter" => crate::parser::Parser::parse(&name, inner, &mut setter)?,
+            "vis" => crate::parser::Parser::parse(&name, inner, &mut vis)?,
+            "attributes" => crate::parser::Parser::parse(&name, inner, &mut attributes)?,
+            other => {
+              return Err(
+                ::darling::Error::unknown_field_with_alts(
+                  other,
+                  &[
+                    "skip",
+                    "type",
+                    "nested",
+                    "rename",
+                    "parent",
+                    "from",
+                    "into",
+                    "validate",
+                    "getter",
+                    "setter",
+                    "vis",
+                    "attributes",
+                  ],
+                )
+                .with_span(inner),
+              );
+            }
+          }
+        }
+        syn::NestedMeta::Lit(ref inner) => {
+          return Err(::darling::Error::unsupported_format("literal").with_span(inner));
+        }
+      }
+    }
+
+    for (name, converter) in [("from", &from.1), ("into", &into.1)] {
+      if let Some(FieldConverter {
+        expr: Some(expr),
+        func,
+        each,
+        ..
+      }) = converter
+      {
+        if func.is_some() || *each || nested.0 {
+          return Err(
+            ::darling::Error::custom(format!(
+              "{}(expr) cannot be specified with fn, each or nested",
+              name
+            ))
+            .with_span(expr),
+          );
+        }
+      }
+    }
+
+    if let Some(nested) = &nested.1 {
+      if typ.0 {
+        return Err(
+          ::darling::Error::custom("nested and type cannot be specified at the same time")
+            .with_span(nested),
+        );
+      }
+      for (name, converter) in [("from", &from.1), ("into", &into.1)] {
+        if matches!(converter, Some(c) if c.func.is_some() || c.each) {
+          return Err(
+            ::darling::Error::custom(format!(
+              "nested cannot be specified with {}(fn) or {}(each)",
+              name, name
+            ))
+            .with_span(nested),
+          );
+        }
+      }
+    }
+
+    Ok(Some(Field {
+      src_ty: field.ty.clone(),
+      src_vis: field.vis.clone(),
+      skip: skip.1,
+      vis: vis.1,
+      nested: nested.1.is_some(),
+      typ: nested.1.or(typ.1),
+      rename: rename.1,
+      parent: parent.1,
+      getter: getter.1.unwrap_or_default(),
+      setter: setter.1.unwrap_or_default(),
+      from: from.1,
+      into: into.1,
+      validate: validate.1,
+      attributes: attributes.1.unwrap_or_default(),
+      named: field.ident.is_some(),
+      implicit: false,
+      docs: Vec::new(),
+      cfgs: Vec::new(),
+    }))
+  }
+}
+
+/// Collects the fields of a struct or an enum variant into the field lists of
+/// every target, the `targets` are pairs of target name and its fields, the fields
+/// are kept in declaration order.
+pub(crate) fn collect_fields(
+  fields: &syn::Fields,
+  mut targets: Vec<(&str, &mut Vec<(String, Field)>)>,
+) -> darling::Result<()> {
+  if targets.is_empty() {
+    return Ok(());
+  }
+
+  let num_targets = targets.len();
+  for (idx, field) in fields.iter().enumerate() {
+    let key = field
+      .ident
+      .as_ref()
+      .map(ToString::to_string)
+      .unwrap_or_else(|| idx.to_string());
+    if field.ident.is_none() && field.attrs.iter().any(|attr| attr.path.is_ident("cfg")) {
+      return Err(
+        darling::Error::custom("cfg is only supported for named fields").with_span(field),
+      );
+    }
+    for attr in &field.attrs {
+      if !attr.path.is_ident("fromit") {
+        continue;
+      }
+
+      let f = match Field::from_attr(field, attr)? {
+        Some(f) => f,
+        None => continue,
+      };
+      if num_targets > 1 && f.parent.is_none() {
+        return Err(
+          ::darling::Error::custom(
+            "parent must be specified when there are more than one struct needed to be generated",
+          )
+          .with_span(attr),
+        );
+      }
+
+      let fields = if num_targets > 1 {
+        let parent = f.parent.as_ref().unwrap().to_string();
+        match targets.iter_mut().find(|(name, _)| *name == parent) {
+          Some((_, fields)) => fields,
+          None => {
+            return Err(
+              darling::Error::custom(format!("Does not have parent {}", parent))
+                .with_span(f.parent.as_ref().unwrap()),
+            )
+          }
+        }
+      } else {
+        &mut targets[0].1
+      };
+
+      match fields.iter_mut().find(|(name, _)| *name == key) {
+        Some((_, old_f)) => old_f.merge(f),
+        None => fields.push((key.clone(), f)),
+      }
+    }
+
+    for (parent, fields) in targets.iter_mut() {
+      let f = match fields.iter_mut().find(|(name, _)| *name == key) {
+        Some((_, f)) => f,
+        None => {
+          fields.push((key.clone(), Field::new(field, parent)));
+          &mut fields.last_mut().unwrap().1
+        }
+      };
+      f.docs = attrs_named(&field.attrs, "doc");
+      f.cfgs = attrs_named(&field.attrs, "cfg");
+    }
+  }
+  Ok(())
+}
+
+/// Returns the attributes with the given name, e.g. the doc comments of `doc`.
+pub(crate) fn attrs_named(attrs: &[syn::Attribute], name: &str) -> Vec<syn::Attribute> {
+  attrs
+    .iter()
+    .filter(|attr| attr.path.is_ident(name))
+    .cloned()
+    .collect()
+}
+
+/// Returns the conversion of the given value, which is a `Result` in `try_` mode.
+/// `value` must already be borrowed or moved according to the final style of the
+/// field. A `nested` field is converted by the `From`/`TryFrom` of the type on the
+/// other side. With `context`, `fn` receives `ctx` as the second argument. `expr`
+/// ignores `value`.
+fn convert_field(
+  converter: Option<&FieldConverter>,
+  nested: bool,
+  try_: bool,
+  context: bool,
+  value: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+  if let Some(expr) = converter.and_then(|c| c.expr.as_ref()) {
+    return quote!((#expr));
+  }
+  match converter.and_then(|c| c.func.as_ref()) {
+    Some(f) if context => quote!(#f(#value, &mut *ctx)),
+    Some(f) => quote!(#f(#value)),
+    None if nested && try_ => quote!(::core::convert::TryFrom::try_from(#value)),
+    None if nested => quote!(::core::convert::From::from(#value)),
+    None if try_ => quote!(::core::convert::TryInto::try_into(#value)),
+    None => quote!(::core::convert::Into::into(#value)),
+  }
+}
+
+/// Returns the array type of the given type, if any.
+fn array_of(ty: &syn::Type) -> Option<&syn::TypeArray> {
+  match ty {
+    syn::Type::Array(arr) => Some(arr),
+    syn::Type::Group(g) => array_of(&g.elem),
+    syn::Type::Paren(p) => array_of(&p.elem),
+    _ => None,
+  }
+}
+
+/// Returns whether the given array lengths are the same, `None` when they cannot be
+/// compared, e.g. `N` and `M`.
+fn same_len(a: &syn::Expr, b: &syn::Expr) -> Option<bool> {
+  if a.to_token_stream().to_string() == b.to_token_stream().to_string() {
+    return Some(true);
+  }
+  let value = |expr: &syn::Expr| match expr {
+    syn::Expr::Lit(syn::ExprLit {
+      lit: syn::Lit::Int(lit),
+      ..
+    }) => lit.base10_parse::<u128>().ok(),
+    _ => None,
+  };
+  Some(value(a)? == value(b)?)
+}
+
+/// Returns the error `e` of the element at the given `location`, e.g. `index 1`, which
+/// is displayed after the location and returned by `source()`.
+fn element_error(location: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+  quote! {
+    {
+      #[derive(Debug)]
+      struct ElementError {
+        location: ::std::string::String,
+        source: ::std::boxed::Box<
+          dyn ::std::error::Error + ::core::marker::Send + ::core::marker::Sync + 'static,
+        >,
+      }
+
+      impl ::core::fmt::Display for ElementError {
+        fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+          ::core::write!(f, "{}: {}", self.location, self.source)
+        }
+      }
+
+      impl ::std::error::Error for ElementError {
+        fn source(&self) -> ::core::option::Option<&(dyn ::std::error::Error + 'static)> {
+          ::core::option::Option::Some(&*self.source)
+        }
+      }
+
+      ElementError {
+        location: #location,
+        source: ::core::convert::Into::into(e),
+      }
+    }
+  }
+}
+
+/// Returns the conversion of the given container of type `from_ty` to `to_ty`,
+/// which converts the elements one by one. When fallible, the conversion is a
+/// `Result` of the first error, wrapped with the index or the key of the element
+/// if the `report` locates it, see [`element_error`].
+fn convert_each(
+  converter: &FieldConverter,
+  from_ty: &syn::Type,
+  to_ty: &syn::Type,
+  report: Option<&ErrorReport>,
+  style: Style,
+  context: bool,
+  value: proc_macro2::TokenStream,
+) -> syn::Result<proc_macro2::TokenStream> {
+  let container = Container::from_type(from_ty).ok_or_else(|| {
+    syn::Error::new_spanned(
+      from_ty,
+      "each is only supported for Option, Vec, VecDeque, BTreeMap, HashMap, Box and arrays",
+    )
+  })?;
+  let try_ = report.is_some();
+  let locates = matches!(report, Some(report) if report.locates());
+  let elem = convert_field(Some(converter), false, try_, context, quote!(v));
+  let index_error = element_error(quote!(::std::format!("index {}", i)));
+  let seq = |value: proc_macro2::TokenStream| match report {
+    None => quote! {
+      ::core::iter::Iterator::collect(
+        ::core::iter::Iterator::map(::core::iter::IntoIterator::into_iter(#value), |v| #elem)
+      )
+    },
+    Some(_) if locates => quote! {
+      ::core::iter::Iterator::collect::<::core::result::Result<_, _>>(
+        ::core::iter::Iterator::map(
+          ::core::iter::Iterator::enumerate(::core::iter::IntoIterator::into_iter(#value)),
+          |(i, v)| #elem.map_err(|e| #index_error),
+        )
+      )
+    },
+    Some(_) => quote! {
+      ::core::iter::Iterator::collect::<::core::result::Result<_, _>>(
+        ::core::iter::Iterator::map(::core::iter::IntoIterator::into_iter(#value), |v| #elem)
+      )
+    },
+  };
+
+  Ok(match container {
+    Container::Option if try_ => quote! {
+      match #value {
+        ::core::option::Option::Some(v) => ::core::result::Result::map(#elem, ::core::option::Option::Some),
+        ::core::option::Option::None => ::core::result::Result::Ok(::core::option::Option::None),
+      }
+    },
+    Container::Option => quote! {
+      match #value {
+        ::core::option::Option::Some(v) => ::core::option::Option::Some(#elem),
+        ::core::option::Option::None => ::core::option::Option::None,
+      }
+    },
+    Container::Box => {
+      let inner = match style {
+        Style::Ref => quote!(&**#value),
+        Style::Move => quote!(*#value),
+      };
+      let elem = convert_field(Some(converter), false, try_, context, inner);
+      if try_ {
+        quote!(::core::result::Result::map(#elem, ::std::boxed::Box::new))
+      } else {
+        quote!(::std::boxed::Box::new(#elem))
+      }
+    }
+    Container::Seq => seq(value),
+    Container::Array => {
+      let to_arr = array_of(to_ty);
+      let elem_ty = match to_arr {
+        Some(arr) => {
+          let elem_ty = &arr.elem;
+          quote!(#elem_ty)
+        }
+        None => quote!(_),
+      };
+      // the lengths are checked here when they can be compared, otherwise at runtime
+      let checked = match (array_of(from_ty), to_arr) {
+        (Some(from_arr), Some(to_arr)) => match same_len(&from_arr.len, &to_arr.len) {
+          Some(true) => None,
+          Some(false) => {
+            return Err(syn::Error::new_spanned(
+              to_ty,
+              "each needs arrays of the same length",
+            ))
+          }
+          None if locates => Some(&to_arr.len),
+          None => {
+            return Err(syn::Error::new_spanned(
+              to_ty,
+              "each cannot compare the lengths of the arrays, use the same length, or try_from with the default error or `error = \"auto\"`",
+            ))
+          }
+        },
+        _ => None,
+      };
+      let seq = seq(value);
+      let to_array = quote! {
+        match ::core::convert::TryFrom::try_from(each) {
+          ::core::result::Result::Ok(arr) => arr,
+          ::core::result::Result::Err(_) => ::core::unreachable!(),
+        }
+      };
+      match checked {
+        Some(len) => quote! {
+          match #seq {
+            ::core::result::Result::Ok(each) => {
+              let each: ::std::vec::Vec<#elem_ty> = each;
+              let found = each.len();
+              ::core::result::Result::map_err(
+                ::core::convert::TryFrom::try_from(each),
+                |_| -> ::std::boxed::Box<
+                  dyn ::std::error::Error + ::core::marker::Send + ::core::marker::Sync + 'static,
+                > {
+                  ::core::convert::From::from(::std::format!(
+                    "expected {} elements, found {}",
+                    #len,
+                    found
+                  ))
+                },
+              )
+            }
+            ::core::result::Result::Err(e) => {
+              ::core::result::Result::Err(::core::convert::Into::into(e))
+            }
+          }
+        },
+        None if try_ => quote! {
+          ::core::result::Result::map(#seq, |each: ::std::vec::Vec<#elem_ty>| #to_array)
+        },
+        None => quote! {
+          {
+            let each: ::std::vec::Vec<#elem_ty> = #seq;
+            #to_array
+          }
+        },
+      }
+    }
+    Container::Map => {
+      let key = match style {
+        Style::Ref => quote!(::core::clone::Clone::clone(k)),
+        Style::Move => quote!(k),
+      };
+      match report {
+        None => quote! {
+          ::core::iter::Iterator::collect(
+            ::core::iter::Iterator::map(::core::iter::IntoIterator::into_iter(#value), |(k, v)| (#key, #elem))
+          )
+        },
+        Some(_) => {
+          let err = if locates {
+            element_error(quote!(::std::format!("key {:?}", k)))
+          } else {
+            quote!(e)
+          };
+          quote! {
+            ::core::iter::Iterator::collect::<::core::result::Result<_, _>>(
+              ::core::iter::Iterator::map(::core::iter::IntoIterator::into_iter(#value), |(k, v)| match #elem {
+                ::core::result::Result::Ok(v) => ::core::result::Result::Ok((#key, v)),
+                ::core::result::Result::Err(e) => ::core::result::Result::Err(#err),
+              })
+            )
+          }
+        }
+      }
+    }
+  })
+}
+
+/// Returns how to access a value bound by a `match` on a source of `outer` style,
+/// when the field is converted in `inner` style.
+pub(crate) fn binding_access(
+  outer: Style,
+  inner: Style,
+  binding: &syn::Ident,
+) -> proc_macro2::TokenStream {
+  match (outer, inner) {
+    (Style::Ref, Style::Ref) | (Style::Move, Style::Move) => quote!(#binding),
+    (Style::Move, Style::Ref) => quote!(&#binding),
+    (Style::Ref, Style::Move) => quote!(*#binding),
+  }
+}
+
+/// Returns the member used to access the field of the source.
+pub(crate) fn src_member(key: &str, named: bool) -> syn::Member {
+  if named {
+    syn::Member::Named(format_ident!("{}", key))
+  } else {
+    syn::Member::Unnamed(key.parse::<usize>().unwrap().into())
+  }
+}
+
+/// Returns the name of the member, the index for a tuple field.
+pub(crate) fn member_name(member: &syn::Member) -> String {
+  match member {
+    syn::Member::Named(ident) => ident.to_string(),
+    syn::Member::Unnamed(idx) => idx.index.to_string(),
+  }
+}
+
+/// Returns the identifier used to bind the field in a `match` arm.
+pub(crate) fn binding_ident(key: &str) -> syn::Ident {
+  format_ident!("__{}", key.trim_start_matches("r#"))
+}
@@ -8,10 +8,10 @@ pub(super) fn generate_from(
   if opts.converter.try_from.is_none() && opts.converter.from.is_none() {
     return Ok(quote!());
   }
-  let (try_, style, error) = match (&opts.converter.try_from, &opts.converter.from) {
+  let (try_, style, context) = match (&opts.converter.try_from, &opts.converter.from) {
     (None, None) => return Ok(quote!()),
-    (None, Some(from)) => (false, *from, None),
-    (Some(try_from), None) => (true, try_from.style, try_from.error.clone()),
+    (None, Some(from)) => (None, from.style, from.context.as_ref()),
+    (Some(try_from), None) => (Some(try_from), try_from.style, try_from.context.as_ref()),
     (Some(_), Some(_)) => {
       return Err(syn::Error::new_spanned(
         &opts.name,
@@ -20,118 +20,334 @@ pub(super) fn generate_from(
     }
   };
 
-  let mut try_from_fields = Vec::new();
-  let mut ctr = 0;
-  if let Some(extra) = &opts.extra {
-    for field in extra.fields.values() {
-      let name = field
-        .name
-        .clone()
-        .unwrap_or_else(|| format_ident!("{}", ctr));
-      ctr += usize::from(field.named);
-      let default = field
-        .default
-        .as_ref()
-        .map(|d| quote!(#d()))
-        .unwrap_or(quote! { ::core::default::Default::default() });
-
-      try_from_fields.push(quote! {
-          #name: #default,
-      });
-    }
-  }
-
-  for (src_name, field) in &opts.fields {
-    if field.skip.is_some() {
-      continue;
-    }
-    let name = field.rename.clone().unwrap_or_else(|| {
-      if field.named {
-        format_ident!("{}", src_name)
-      } else {
-        format_ident!("{}", ctr)
-      }
-    });
-    let src_name = format_ident!("{}", src_name);
-    ctr += usize::from(field.named);
-
-    match &field.from {
-      Some(from) => {
-        let final_style = from.style.unwrap_or(style);
-
-        let converter = if try_ {
-          from
-            .func
-            .as_ref()
-            .map(|f| quote!(#f(#final_style s.#src_name)?))
-            .unwrap_or_else(|| {
-              quote! {
-                  ::core::convert::TryInto::try_into(#final_style s.#src_name)?
-              }
-            })
-        } else {
-          from
-            .func
-            .as_ref()
-            .map(|f| quote!(#f(#final_style s.#src_name)))
-            .unwrap_or_else(|| {
-              quote! {
-                  ::core::convert::Into::into(#final_style s.#src_name)
-              }
-            })
-        };
-        try_from_fields.push(quote! {
-            #name: #converter,
-        });
-      }
-      None => {
-        if try_ {
-          try_from_fields.push(quote! {
-              #name: ::core::convert::TryInto::try_into(#style s.#src_name)?,
-          });
-        } else {
-          try_from_fields.push(quote! {
-              #name: ::core::convert::Into::into(#style s.#src_name),
-          });
-        }
-      }
-    }
+  let name = &opts.name;
+  if try_.is_none()
+    && opts
+      .fields
+      .iter()
+      .any(|(_, f)| f.skip.is_none() && f.validate.is_some())
+  {
+    return Err(syn::Error::new_spanned(
+      name,
+      "validate is only checked by try_from, use `converter(try_from())` instead of `from`",
+    ));
   }
+  let mut errors = try_
+    .map(|try_| Errors::new(try_, name, src_name))
+    .transpose()?;
+  let body = match &opts.variants {
+    Some(variants) => generate_from_variants(
+      src_name,
+      opts,
+      variants,
+      errors.as_mut(),
+      style,
+      context.is_some(),
+    )?,
+    None => generate_from_fields(
+      opts,
+      errors.as_mut(),
+      style,
+      context.is_some(),
+      &quote!(Self),
+    )?,
+  };
 
   let final_impl_generics = &final_generics.final_impl_generics;
   let self_ty_generics = &final_generics.ty_generics;
-  let final_where_clause = &final_generics.final_where_clause;
+  let final_where_clause =
+    &conversion_where_clause(opts, final_generics, style, false, errors.as_ref())?;
   let src_ty_generics = &final_generics.src_ty_generics;
+  // A view borrows the source for its lifetime, the other ref-style impls for a
+  // lifetime of their own.
+  let (style, impl_generics) = match &opts.view {
+    Some(view) => (
+      view.source_ref(&final_generics.src_generics),
+      final_impl_generics.clone(),
+    ),
+    None => conversion_generics(style, &final_generics.final_generics),
+  };
 
-  let name = &opts.name;
-  if try_ {
-    let error = error.map(|t| quote!(#t)).unwrap_or_else(|| {
-      quote!(
-        ::std::boxed::Box<
-          dyn ::std::error::Error + ::core::marker::Send + ::core::marker::Sync + 'static,
-        >
-      )
+  if let Some(context) = context {
+    let vis = &opts.vis;
+    return Ok(match errors {
+      Some(errors) => {
+        let error = errors.error_type();
+        let generated_error = errors.generate(vis, src_name, name);
+        quote! {
+            #generated_error
+
+            impl #impl_generics #name #self_ty_generics #final_where_clause {
+                /// Converts the source, the field functions also receive the context.
+                #[allow(unused_variables)]
+                #vis fn try_from_with(s: #style #src_name #src_ty_generics, ctx: &mut #context) -> ::core::result::Result<Self, #error> {
+                    ::core::result::Result::Ok(#body)
+                }
+            }
+        }
+      }
+      None => quote! {
+          impl #impl_generics #name #self_ty_generics #final_where_clause {
+              /// Converts the source, the field functions also receive the context.
+              #[allow(unused_variables)]
+              #vis fn from_with(s: #style #src_name #src_ty_generics, ctx: &mut #context) -> Self {
+                  #body
+              }
+          }
+      },
     });
+  }
+
+  if let Some(errors) = errors {
+    let error = errors.error_type();
+    let generated_error = errors.generate(&opts.vis, src_name, name);
     Ok(quote! {
-        impl #final_impl_generics ::core::convert::TryFrom<#style #src_name #src_ty_generics> for #name #self_ty_generics #final_where_clause {
+        #generated_error
+
+        impl #impl_generics ::core::convert::TryFrom<#style #src_name #src_ty_generics> for #name #self_ty_generics #final_where_clause {
             type Error = #error;
 
             fn try_from(s: #style #src_name #src_ty_generics) -> ::core::result::Result<Self, Self::Error> {
-                ::core::result::Result::Ok(Self {
-                    #(#try_from_fields)*
-                })
+                ::core::result::Result::Ok(#body)
             }
         }
     })
   } else {
     Ok(quote! {
-        impl #final_impl_generics ::core::convert::From<#style #src_name #src_ty_generics> for #name #self_ty_generics #final_where_clause {
+        impl #impl_generics ::core::convert::From<#style #src_name #src_ty_generics> for #name #self_ty_generics #final_where_clause {
             fn from(s: #style #src_name #src_ty_generics) -> Self {
-                Self {
-                    #(#try_from_fields)*
-                }
+                #body
             }
         }
     })
   }
 }
+
+fn generate_from_fields(
+  opts: &StructOpts,
+  mut errors: Option<&mut Errors>,
+  style: Style,
+  context: bool,
+  ctor: &proc_macro2::TokenStream,
+) -> syn::Result<proc_macro2::TokenStream> {
+  let members = opts.members()?;
+  let mut values = vec![None; members.len()];
+  let mut stmts = Vec::new();
+  // The extra fields computed from the source borrow it, so they are computed
+  // before any field is moved out of it.
+  let mut computed = Vec::new();
+  for (idx, (name, member)) in members.iter().enumerate() {
+    let Member::Extra(field) = member else {
+      continue;
+    };
+    if field.from.is_none() {
+      values[idx] = Some(field.convert_from(None, style, context));
+      continue;
+    }
+    let report = errors.as_deref_mut().map(|errors| {
+      let key = member_name(name);
+      errors.report(error_variant(&key), key)
+    });
+    let value = field.convert_from(report.as_ref(), style, context);
+    values[idx] = Some(match errors.as_deref() {
+      Some(errors) if errors.collects() => errors.bind(&mut stmts, &[], value),
+      _ => {
+        let local = format_ident!("__fromit_extra_{}", idx);
+        computed.push(quote!(let #local = #value;));
+        quote!(#local)
+      }
+    });
+  }
+
+  // The fields computed by `from(expr)` read the source, so they come first too.
+  let (exprs, fields): (Vec<_>, Vec<_>) = members
+    .iter()
+    .enumerate()
+    .filter_map(|(idx, (name, member))| match member {
+      Member::Field(key, field) => Some((idx, name, key, field)),
+      Member::Extra(_) => None,
+    })
+    .partition(|(_, _, _, field)| matches!(&field.from, Some(from) if from.expr.is_some()));
+  for (idx, name, key, field) in exprs.into_iter().chain(fields) {
+    let src_name = src_member(key, field.named);
+    let final_style = field
+      .from
+      .as_ref()
+      .and_then(|from| from.style)
+      .unwrap_or(style);
+    let report = errors
+      .as_deref_mut()
+      .map(|errors| errors.report(error_variant(key), key.to_string()));
+    let converter = field.convert_from(
+      report.as_ref(),
+      final_style,
+      context,
+      quote!(#final_style s.#src_name),
+    )?;
+    let converter = match (&field.validate, &report) {
+      (Some(validate), Some(report)) => validated(
+        report,
+        field.target_ty(),
+        |value| {
+          validate.check(
+            &validation_error_name(&opts.name),
+            &member_name(name),
+            value,
+          )
+        },
+        converter,
+      ),
+      _ => converter,
+    };
+    let value = match errors.as_deref() {
+      Some(errors) => errors.bind(&mut stmts, &field.cfgs, converter),
+      None => converter,
+    };
+    values[idx] = Some(match (&field.from, errors.as_deref()) {
+      (_, Some(errors)) if errors.collects() => value,
+      (Some(FieldConverter { expr: Some(_), .. }), _) => {
+        let local = format_ident!("__fromit_expr_{}", idx);
+        let cfgs = &field.cfgs;
+        computed.push(quote!(#(#cfgs)* let #local = #value;));
+        quote!(#local)
+      }
+      _ => value,
+    });
+  }
+
+  let names = members.iter().map(|(name, _)| name);
+  let cfgs = members.iter().map(|(_, member)| member.cfgs());
+  let value = quote! {
+    #ctor {
+      #(#(#cfgs)* #names: #values,)*
+    }
+  };
+  let value = match errors {
+    Some(errors) => errors.finish(stmts, value),
+    None => value,
+  };
+  Ok(if computed.is_empty() {
+    value
+  } else {
+    quote! {
+      {
+        #(#computed)*
+        #value
+      }
+    }
+  })
+}
+
+/// Returns the value of a view built from the source `s`.
+pub(crate) fn generate_view_value(opts: &StructOpts) -> syn::Result<proc_macro2::TokenStream> {
+  let name = &opts.name;
+  generate_from_fields(opts, None, Style::Ref, false, &quote!(#name))
+}
+
+fn generate_from_variants(
+  src_name: &syn::Ident,
+  opts: &StructOpts,
+  variants: &[Variant],
+  mut errors: Option<&mut Errors>,
+  style: Style,
+  context: bool,
+) -> syn::Result<proc_macro2::TokenStream> {
+  let mut arms = Vec::with_capacity(variants.len());
+  for variant in variants {
+    let src_variant = &variant.src_name;
+    if variant.skip {
+      let Some(errors) = errors.as_deref_mut() else {
+        return Err(syn::Error::new_spanned(
+          src_variant,
+          format!(
+            "variant `{}` is skipped by `{}`, use `try_from` instead of `from`",
+            src_variant, opts.name
+          ),
+        ));
+      };
+      let msg = format!(
+        "variant `{}` is not supported by `{}`",
+        src_variant, opts.name
+      );
+      let err = errors.unsupported(&msg);
+      arms.push(quote! {
+        #src_name::#src_variant { .. } => return ::core::result::Result::Err(#err),
+      });
+      continue;
+    }
+
+    let name = variant.name();
+    let mut bindings = Vec::new();
+    let mut fields = Vec::new();
+    let mut stmts = Vec::new();
+    let mut skipped = false;
+    for (src_field, field) in variant.fields.iter() {
+      let binding = binding_ident(src_field);
+      if field.skip.is_some() {
+        skipped = true;
+        if !field.named {
+          bindings.push(quote!(_));
+        }
+        continue;
+      }
+
+      let final_style = field
+        .from
+        .as_ref()
+        .and_then(|from| from.style)
+        .unwrap_or(style);
+      let report = errors.as_deref_mut().map(|errors| {
+        errors.report(
+          format_ident!("{}{}", src_variant, error_variant(src_field)),
+          format!("{}.{}", src_variant, src_field),
+        )
+      });
+      let converter = field.convert_from(
+        report.as_ref(),
+        final_style,
+        context,
+        binding_access(style, final_style, &binding),
+      )?;
+      let cfgs = &field.cfgs;
+      let converter = match errors.as_deref() {
+        Some(errors) => errors.bind(&mut stmts, cfgs, converter),
+        None => converter,
+      };
+      if field.named {
+        let src_field = format_ident!("{}", src_field);
+        let field_name = field.rename.clone().unwrap_or_else(|| src_field.clone());
+        bindings.push(quote!(#(#cfgs)* #src_field: #binding));
+        fields.push(quote!(#(#cfgs)* #field_name: #converter));
+      } else {
+        bindings.push(quote!(#binding));
+        fields.push(converter);
+      }
+    }
+
+    let (pattern, value) = match variant.style {
+      darling::ast::Style::Struct => {
+        let rest = skipped.then(|| quote!(..));
+        (
+          quote!(#src_name::#src_variant { #(#bindings,)* #rest }),
+          quote!(Self::#name { #(#fields,)* }),
+        )
+      }
+      darling::ast::Style::Tuple => (
+        quote!(#src_name::#src_variant(#(#bindings,)*)),
+        quote!(Self::#name(#(#fields,)*)),
+      ),
+      darling::ast::Style::Unit => (quote!(#src_name::#src_variant), quote!(Self::#name)),
+    };
+    let value = match errors.as_deref() {
+      Some(errors) => errors.finish(stmts, value),
+      None => value,
+    };
+    arms.push(quote!(#pattern => #value,));
+  }
+
+  Ok(quote! {
+    match s {
+      #(#arms)*
+    }
+  })
+}
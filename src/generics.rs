@@ -0,0 +1,456 @@
+use std::collections::HashSet;
+
+use super::*;
+
+/// Collects the identifiers and the lifetimes in the given tokens, which include the
+/// generic params referenced by a type, e.g. `T` and `'a` in `&'a [T]`.
+fn collect_names(tokens: proc_macro2::TokenStream, names: &mut HashSet<String>) {
+  let mut lifetime = false;
+  for token in tokens {
+    match token {
+      proc_macro2::TokenTree::Group(group) => collect_names(group.stream(), names),
+      proc_macro2::TokenTree::Ident(ident) if lifetime => {
+        names.insert(format!("'{}", ident));
+      }
+      proc_macro2::TokenTree::Ident(ident) => {
+        names.insert(ident.to_string());
+      }
+      proc_macro2::TokenTree::Punct(punct) => {
+        lifetime = punct.as_char() == '\'';
+        continue;
+      }
+      proc_macro2::TokenTree::Literal(_) => {}
+    }
+    lifetime = false;
+  }
+}
+
+/// Returns the names in the given tokens, see [`collect_names`].
+fn names_of(tokens: impl ToTokens) -> HashSet<String> {
+  let mut names = HashSet::new();
+  collect_names(tokens.to_token_stream(), &mut names);
+  names
+}
+
+/// Returns a lifetime named `name` which is not a lifetime of the given generics,
+/// e.g. `'a`, or `'a1` if `'a` is taken.
+pub(crate) fn fresh_lifetime(generics: &syn::Generics, name: &str) -> syn::Lifetime {
+  let taken = |lifetime: &str| generics.lifetimes().any(|l| l.lifetime.ident == lifetime);
+  let ident = std::iter::once(name.to_string())
+    .chain((1..).map(|idx| format!("{}{}", name, idx)))
+    .find(|candidate| !taken(candidate))
+    .unwrap();
+  syn::Lifetime::new(&format!("'{}", ident), proc_macro2::Span::call_site())
+}
+
+/// Returns the type of the source or the target taken by a conversion impl of the
+/// given style, with `&'__fromit` for the ref style, and the impl generics declaring
+/// that lifetime.
+pub(crate) fn conversion_generics(
+  style: Style,
+  generics: &syn::Generics,
+) -> (proc_macro2::TokenStream, proc_macro2::TokenStream) {
+  match style {
+    Style::Move => (quote!(), generics.split_for_impl().0.to_token_stream()),
+    Style::Ref => {
+      let lifetime = fresh_lifetime(generics, "__fromit");
+      let mut generics = generics.clone();
+      generics.params.insert(
+        0,
+        syn::GenericParam::Lifetime(syn::LifetimeDef::new(lifetime.clone())),
+      );
+      (
+        quote!(&#lifetime),
+        generics.split_for_impl().0.to_token_stream(),
+      )
+    }
+  }
+}
+
+/// Replaces the params in the given tokens by their substitutions.
+pub(crate) fn substitute_tokens(
+  tokens: proc_macro2::TokenStream,
+  substitutions: &[(syn::Ident, syn::Type)],
+) -> proc_macro2::TokenStream {
+  tokens
+    .into_iter()
+    .map(|token| match token {
+      proc_macro2::TokenTree::Group(group) => {
+        let mut substituted = proc_macro2::Group::new(
+          group.delimiter(),
+          substitute_tokens(group.stream(), substitutions),
+        );
+        substituted.set_span(group.span());
+        proc_macro2::TokenTree::Group(substituted).into()
+      }
+      proc_macro2::TokenTree::Ident(ident) => {
+        match substitutions.iter().find(|(param, _)| *param == ident) {
+          // the type is grouped, so `T = &'a str` in `&T` stays a reference to it
+          Some((_, ty)) => proc_macro2::TokenTree::Group(proc_macro2::Group::new(
+            proc_macro2::Delimiter::None,
+            ty.to_token_stream(),
+          ))
+          .into(),
+          None => proc_macro2::TokenTree::Ident(ident).into(),
+        }
+      }
+      token => proc_macro2::TokenStream::from(token),
+    })
+    .collect()
+}
+
+/// Replaces the params in the given syntax node by their substitutions.
+pub(crate) fn substitute<T: ToTokens + syn::parse::Parse>(
+  node: &T,
+  substitutions: &[(syn::Ident, syn::Type)],
+) -> syn::Result<T> {
+  if substitutions.is_empty() {
+    return syn::parse2(node.to_token_stream());
+  }
+  syn::parse2(substitute_tokens(node.to_token_stream(), substitutions))
+}
+
+/// Removes the substituted params from the generics, whose bounds are kept as where
+/// predicates of their substitutions, and substitutes them in the rest.
+pub(crate) fn substitute_generics(
+  generics: &syn::Generics,
+  substitutions: &[(syn::Ident, syn::Type)],
+) -> syn::Result<syn::Generics> {
+  if substitutions.is_empty() {
+    return Ok(generics.clone());
+  }
+  let mut substituted = syn::Generics::default();
+  let mut predicates = Vec::new();
+  for param in &generics.params {
+    match param {
+      syn::GenericParam::Type(p) => match substitutions.iter().find(|(name, _)| *name == p.ident) {
+        Some((_, ty)) if !p.bounds.is_empty() => {
+          let bounds = &p.bounds;
+          predicates.push(quote!(#ty: #bounds));
+        }
+        Some(_) => {}
+        None => substituted.params.push(substitute(param, substitutions)?),
+      },
+      param => substituted.params.push(substitute(param, substitutions)?),
+    }
+  }
+  let predicates = generics
+    .where_clause
+    .iter()
+    .flat_map(|w| w.predicates.iter())
+    .map(ToTokens::to_token_stream)
+    .chain(predicates)
+    .map(|predicate| {
+      syn::parse2::<syn::WherePredicate>(substitute_tokens(predicate, substitutions))
+    })
+    .collect::<syn::Result<Vec<_>>>()?;
+  if !predicates.is_empty() {
+    substituted
+      .make_where_clause()
+      .predicates
+      .extend(predicates);
+  }
+  Ok(substituted)
+}
+
+impl StructOpts {
+  /// Replaces the params of `bound(substitute)` in the types of the fields.
+  pub(crate) fn substitute_fields(&mut self, src_generics: &syn::Generics) -> darling::Result<()> {
+    let Some(bound) = &self.bound else {
+      return Ok(());
+    };
+    let substitutions = bound.substitutions()?;
+    if substitutions.is_empty() {
+      return Ok(());
+    }
+    if let Some((param, _)) = substitutions
+      .iter()
+      .find(|(param, _)| !src_generics.type_params().any(|p| p.ident == *param))
+    {
+      return Err(
+        darling::Error::custom(format!(
+          "the source does not have generic parameter `{}`",
+          param
+        ))
+        .with_span(param),
+      );
+    }
+    if self.lazy_view || matches!(&self.view, Some(view) if view.split) {
+      return Err(
+        darling::Error::custom("substitute is not supported by lazy_view and view(mut, split)")
+          .with_span(&self.name),
+      );
+    }
+
+    let fields = self
+      .fields
+      .iter_mut()
+      .chain(
+        self
+          .variants
+          .iter_mut()
+          .flatten()
+          .flat_map(|v| v.fields.iter_mut()),
+      )
+      .map(|(_, field)| field);
+    for field in fields {
+      field.src_ty = substitute(&field.src_ty, &substitutions)?;
+      if let Some(typ) = &field.typ {
+        field.typ = Some(substitute(typ, &substitutions)?);
+      }
+    }
+    for field in self.extra.iter_mut().flat_map(|e| e.fields.iter_mut()) {
+      field.src_ty = substitute(&field.src_ty, &substitutions)?;
+    }
+    Ok(())
+  }
+
+  /// Returns the types of the fields kept by the target, including the extra fields
+  /// and the fields of the variants.
+  fn kept_types(&self) -> syn::Result<Vec<syn::Type>> {
+    Ok(match &self.variants {
+      Some(variants) => variants
+        .iter()
+        .filter(|v| !v.skip)
+        .flat_map(|v| v.fields.iter())
+        .filter(|(_, f)| f.skip.is_none())
+        .map(|(_, f)| f.target_ty().clone())
+        .collect(),
+      None => self
+        .members()?
+        .iter()
+        .map(|(_, member)| member.ty().clone())
+        .collect(),
+    })
+  }
+
+  /// Adds a `PhantomData` extra field for the params kept by `bound(auto)` which are
+  /// not referenced by any field, e.g. `U` only bounding `T: Iterator<Item = U>`.
+  pub(crate) fn add_marker(&mut self, src_generics: &syn::Generics) -> darling::Result<()> {
+    if !matches!(&self.bound, Some(bound) if bound.auto) {
+      return Ok(());
+    }
+    let generics = auto_generics(self, src_generics)?;
+    let mut used = HashSet::new();
+    for ty in self.kept_types()? {
+      collect_names(ty.to_token_stream(), &mut used);
+    }
+    let unused = generics
+      .params
+      .iter()
+      .filter(|param| !used.contains(&param_name(param)))
+      .map(|param| match param {
+        syn::GenericParam::Type(p) => {
+          let ident = &p.ident;
+          quote!(#ident)
+        }
+        syn::GenericParam::Lifetime(p) => {
+          let lifetime = &p.lifetime;
+          quote!(&#lifetime ())
+        }
+        syn::GenericParam::Const(p) => {
+          let ident = &p.ident;
+          quote!([(); #ident])
+        }
+      })
+      .collect::<Vec<_>>();
+    if unused.is_empty() {
+      return Ok(());
+    }
+    if self.variants.is_some() {
+      return Err(
+        darling::Error::custom(
+          "bound(auto) keeps generic params which no variant field references, specify bound(extra) instead",
+        )
+        .with_span(&self.name),
+      );
+    }
+
+    let named = self.style == darling::ast::Style::Struct;
+    let position = self.members()?.len();
+    let marker = ExtraField {
+      name: named.then(|| format_ident!("__fromit_marker")),
+      src_ty: syn::parse_quote!(::core::marker::PhantomData<fn() -> (#(#unused,)*)>),
+      src_vis: syn::Visibility::Inherited,
+      vis: None,
+      getter: FieldLevelGetter {
+        ignore: true,
+        ..Default::default()
+      },
+      setter: FieldLevelSetter {
+        ignore: true,
+        ..Default::default()
+      },
+      default: Some(syn::parse_quote!(::core::default::Default::default)),
+      from: None,
+      into: None,
+      placement: Some(Placement::Position(position)),
+      attributes: Default::default(),
+      named,
+    };
+    self
+      .extra
+      .get_or_insert_with(|| Extra {
+        attributes: Default::default(),
+        fields: Vec::new(),
+      })
+      .fields
+      .push(marker);
+    Ok(())
+  }
+}
+
+/// Returns the generics of a target with `bound(auto)`, which are the params of the
+/// source referenced by the kept fields, the params their bounds reference, and the
+/// where predicates only referencing those params, merged with `bound(extra)`.
+pub(crate) fn auto_generics(
+  opts: &StructOpts,
+  src_generics: &syn::Generics,
+) -> syn::Result<syn::Generics> {
+  let params = src_generics
+    .params
+    .iter()
+    .map(|param| (param_name(param), param))
+    .collect::<Vec<_>>();
+  let mut used = HashSet::new();
+  for ty in opts.kept_types()? {
+    collect_names(ty.to_token_stream(), &mut used);
+  }
+  // the bounds of a kept param keep the params they reference
+  loop {
+    let referenced = params
+      .iter()
+      .filter(|(name, _)| used.contains(name))
+      .flat_map(|(_, param)| names_of(param))
+      .filter(|name| !used.contains(name))
+      .collect::<Vec<_>>();
+    if referenced.is_empty() {
+      break;
+    }
+    used.extend(referenced);
+  }
+
+  let mut generics = syn::Generics {
+    params: params
+      .iter()
+      .filter(|(name, _)| used.contains(name))
+      .map(|(_, param)| (*param).clone())
+      .collect(),
+    ..Default::default()
+  };
+  if let Some(where_clause) = &src_generics.where_clause {
+    let predicates = where_clause
+      .predicates
+      .iter()
+      .filter(|predicate| {
+        let names = names_of(predicate);
+        let mentioned = params
+          .iter()
+          .filter(|(name, _)| names.contains(name))
+          .collect::<Vec<_>>();
+        !mentioned.is_empty() && mentioned.iter().all(|(name, _)| used.contains(name))
+      })
+      .cloned()
+      .collect::<syn::punctuated::Punctuated<_, _>>();
+    if !predicates.is_empty() {
+      generics.make_where_clause().predicates = predicates;
+    }
+  }
+  let extra = match &opts.bound {
+    Some(bound) => bound.extra_generics()?,
+    None => syn::Generics::default(),
+  };
+  merge_generics(generics, &extra)
+}
+
+/// Returns the where clause of a conversion impl of the given style, `into` for the
+/// conversion back to the source. With `converter(infer_bounds)`, the bounds of the
+/// field conversions by `Into`/`TryInto` or `From`/`TryFrom` which reference the
+/// params are added, and the bound converting their errors into `errors`.
+pub(crate) fn conversion_where_clause(
+  opts: &StructOpts,
+  final_generics: &FinalGenerics,
+  style: Style,
+  into: bool,
+  errors: Option<&Errors>,
+) -> syn::Result<proc_macro2::TokenStream> {
+  if !opts.converter.infer_bounds {
+    return Ok(final_generics.final_where_clause.to_token_stream());
+  }
+  let mut generics = final_generics.final_generics.clone();
+  let params = generics
+    .params
+    .iter()
+    .map(param_name)
+    .collect::<HashSet<_>>();
+  let lifetime = fresh_lifetime(&generics, "__fromit_field");
+  let error = errors.map(Errors::field_error_type);
+
+  let fields = match &opts.variants {
+    Some(variants) => variants
+      .iter()
+      .filter(|v| !v.skip)
+      .flat_map(|v| v.fields.iter())
+      .map(|(_, field)| field)
+      .collect::<Vec<_>>(),
+    None => opts.fields.iter().map(|(_, field)| field).collect(),
+  };
+  let mut predicates = Vec::new();
+  for field in fields.into_iter().filter(|f| f.skip.is_none()) {
+    let (converter, src_ty, dst_ty) = match into {
+      false => (&field.from, &field.src_ty, field.target_ty()),
+      true => (&field.into, field.target_ty(), &field.src_ty),
+    };
+    if matches!(converter, Some(c) if c.func.is_some() || c.expr.is_some() || c.each) {
+      continue;
+    }
+    let mut names = names_of(src_ty);
+    collect_names(dst_ty.to_token_stream(), &mut names);
+    if names.is_disjoint(&params) {
+      continue;
+    }
+    let (hrtb, value) = match converter.as_ref().and_then(|c| c.style).unwrap_or(style) {
+      Style::Move
+        if src_ty.to_token_stream().to_string() == dst_ty.to_token_stream().to_string() =>
+      {
+        continue
+      }
+      Style::Move => (quote!(), quote!(#src_ty)),
+      Style::Ref => (quote!(for<#lifetime>), quote!(&#lifetime #src_ty)),
+    };
+    let (bound, converted_error) = match (field.nested, &error) {
+      (true, None) => (quote!(#dst_ty: ::core::convert::From<#value>), None),
+      (false, None) => (quote!(#value: ::core::convert::Into<#dst_ty>), None),
+      (true, Some(_)) => (
+        quote!(#dst_ty: ::core::convert::TryFrom<#value>),
+        Some(quote!(<#dst_ty as ::core::convert::TryFrom<#value>>::Error)),
+      ),
+      (false, Some(_)) => (
+        quote!(#value: ::core::convert::TryInto<#dst_ty>),
+        Some(quote!(<#value as ::core::convert::TryInto<#dst_ty>>::Error)),
+      ),
+    };
+    predicates.push(quote!(#hrtb #bound));
+    if let (Some(error), Some(converted_error)) = (&error, converted_error) {
+      predicates.push(quote!(#hrtb #converted_error: ::core::convert::Into<#error>));
+    }
+  }
+
+  if predicates.is_empty() {
+    return Ok(final_generics.final_where_clause.to_token_stream());
+  }
+  let where_clause = generics.make_where_clause();
+  let mut declared = where_clause
+    .predicates
+    .iter()
+    .map(|p| p.to_token_stream().to_string())
+    .collect::<HashSet<_>>();
+  for predicate in predicates {
+    if declared.insert(predicate.to_string()) {
+      where_clause
+        .predicates
+        .push(syn::parse2::<syn::WherePredicate>(predicate)?);
+    }
+  }
+  Ok(where_clause.to_token_stream())
+}
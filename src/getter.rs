@@ -64,7 +64,7 @@ pub(crate) struct AccessorConverter {
 impl AccessorConverter {
   pub(crate) fn to_getter(
     &self,
-    field_name: &syn::Ident,
+    field_name: &syn::Member,
     field_ty: &syn::Type,
     style: Style,
     vis: &syn::Visibility,
@@ -107,118 +107,68 @@ impl AccessorConverter {
   }
 }
 
+/// Returns the name of the getter of a field, or `None` if it has no getter.
+pub(crate) fn getter_name(
+  opts: &StructOpts,
+  field_name: &syn::Member,
+  getter: &FieldLevelGetter,
+) -> Option<syn::Ident> {
+  if getter.ignore {
+    return None;
+  }
+  // a tuple field has no getter unless it can be named by a rename or a prefix
+  match (&getter.rename, &opts.getters.prefix, field_name) {
+    (Some(rename), _, _) => Some(rename.clone()),
+    (None, Some(p), field_name) => Some(format_ident!("{}_{}", p, member_name(field_name))),
+    (None, None, syn::Member::Named(field_name)) => Some(field_name.clone()),
+    (None, None, syn::Member::Unnamed(_)) => None,
+  }
+}
+
 pub(crate) fn generate_getters(
   opts: &StructOpts,
   final_generics: &FinalGenerics,
 ) -> syn::Result<proc_macro2::TokenStream> {
-  if opts.getters.ignore {
+  if opts.getters.ignore || opts.variants.is_some() {
     return Ok(quote!());
   }
-  let mut ctr = 0;
   let mut getters = Vec::new();
-  let mut extra_getters = Vec::new();
-  if let Some(extra) = &opts.extra {
-    for field in extra.fields.values() {
-      if field.getter.ignore {
-        continue;
-      }
-
-      let src_name = field
-        .name
-        .clone()
-        .unwrap_or_else(|| format_ident!("{}", ctr));
-      ctr += usize::from(field.named);
-
-      let field_name = field.name.as_ref().unwrap_or(&src_name);
-      let vis = field.getter.vis.as_ref().unwrap_or_else(|| {
-        opts
-          .getters
-          .vis_all
-          .as_ref()
-          .unwrap_or_else(|| field.vis.as_ref().unwrap_or(&opts.vis))
-      });
-      let fn_name = field.getter.rename.clone().unwrap_or_else(|| {
-        if let Some(p) = &opts.getters.prefix {
-          format_ident!("{}_{}", p, field_name)
-        } else {
-          field_name.clone()
-        }
-      });
-
-      let style = field.getter.style.unwrap_or(opts.getters.style);
-      let field_ty = &field.src_ty;
-      match &field.getter.result {
-        Some(ac) => {
-          extra_getters.push(ac.to_getter(field_name, field_ty, style, vis, &fn_name));
-        }
-        None => {
-          extra_getters.push(quote! {
-            #[inline]
-            #vis fn #fn_name(&self) -> #style #field_ty {
-              #style self.#field_name
-            }
-          });
-        }
-      }
-    }
-  }
-
-  for (src_name, field) in opts.fields.iter() {
-    if field.skip.is_some() || field.getter.ignore {
+  for (field_name, member) in opts.members()? {
+    let getter = member.getter();
+    let Some(fn_name) = getter_name(opts, &field_name, getter) else {
       continue;
-    }
-
-    let src_name = field.rename.clone().unwrap_or_else(|| {
-      if field.named {
-        format_ident!("{}", src_name)
-      } else {
-        format_ident!("{}", ctr)
-      }
-    });
-    ctr += usize::from(field.named);
-    let field_name = field.rename.as_ref().unwrap_or(&src_name);
-    let vis = field.getter.vis.as_ref().unwrap_or_else(|| {
+    };
+    let vis = getter.vis.as_ref().unwrap_or_else(|| {
       opts
         .getters
         .vis_all
         .as_ref()
-        .unwrap_or_else(|| field.vis.as_ref().unwrap_or(&opts.vis))
+        .unwrap_or_else(|| member.vis().unwrap_or(&opts.vis))
     });
-    let fn_name = field.getter.rename.clone().unwrap_or_else(|| {
-      if let Some(p) = &opts.getters.prefix {
-        format_ident!("{}_{}", p, field_name)
-      } else {
-        field_name.clone()
-      }
-    });
-
-    let style = field.getter.style.unwrap_or(opts.getters.style);
-    let field_ty = field.typ.as_ref().unwrap_or(&field.src_ty);
-    match &field.getter.result {
-      Some(ac) => {
-        getters.push(ac.to_getter(field_name, field_ty, style, vis, &fn_name));
-      }
-      None => {
-        getters.push(quote! {
-          #[inline]
-          #vis fn #fn_name(&self) -> #style #field_ty {
-            #style self.#field_name
-          }
-        });
-      }
-    }
+    let style = getter.style.unwrap_or(opts.getters.style);
+    let field_ty = member.ty();
+    let attrs = member.docs().iter().chain(member.cfgs());
+    let getter = match &getter.result {
+      Some(ac) => ac.to_getter(&field_name, field_ty, style, vis, &fn_name),
+      None => quote! {
+        #[inline]
+        #vis fn #fn_name(&self) -> #style #field_ty {
+          #style self.#field_name
+        }
+      },
+    };
+    getters.push(quote!(#(#attrs)* #getter));
   }
 
   let name = &opts.name;
   let impl_generics = &final_generics.impl_generics;
   let self_ty_generics = &final_generics.ty_generics;
   let where_clause = &final_generics.where_clause;
+  let allow = opts.allow_renamed();
   Ok(quote! {
+      #allow
       impl #impl_generics #name #self_ty_generics #where_clause {
-
           #(#getters)*
-
-          #(#extra_getters)*
       }
   })
 }
@@ -8,10 +8,10 @@ pub(crate) fn generate_into(
   if opts.converter.try_into.is_none() && opts.converter.into.is_none() {
     return Ok(quote!());
   }
-  let (try_, style, error) = match (&opts.converter.try_into, &opts.converter.into) {
+  let (try_, style) = match (&opts.converter.try_into, &opts.converter.into) {
     (None, None) => return Ok(quote!()),
-    (None, Some(into)) => (false, *into, None),
-    (Some(try_into), None) => (true, try_into.style, try_into.error.clone()),
+    (None, Some(into)) => (None, *into),
+    (Some(try_into), None) => (Some(try_into), try_into.style),
     (Some(_), Some(_)) => {
       return Err(syn::Error::new_spanned(
         &opts.name,
@@ -20,107 +20,32 @@ pub(crate) fn generate_into(
     }
   };
 
-  let mut try_into_fields = Vec::new();
-  for (src_name, field) in &opts.fields {
-    let src_name = format_ident!("{}", src_name);
-    if let Some(skip) = &field.skip {
-      if let Some(default) = &skip.default {
-        try_into_fields.push(quote! {
-            #src_name: #default(),
-        });
-      } else {
-        try_into_fields.push(quote! {
-            #src_name: ::core::default::Default::default(),
-        });
-      }
-
-      continue;
-    }
-
-    let name = field
-      .rename
-      .clone()
-      .unwrap_or_else(|| format_ident!("{}", src_name));
-
-    let src_name = format_ident!("{}", src_name);
-    match &field.into {
-      Some(into) => {
-        let final_style = into.style.unwrap_or(style);
-
-        let converter = if try_ {
-          into
-            .func
-            .as_ref()
-            .map(|f| quote!(#f(#final_style s.#name)?))
-            .unwrap_or_else(|| {
-              quote! {
-                  ::core::convert::TryInto::try_into(#final_style s.#name)?
-              }
-            })
-        } else {
-          into
-            .func
-            .as_ref()
-            .map(|f| quote!(#f(#final_style s.#name)))
-            .unwrap_or_else(|| {
-              quote! {
-                  ::core::convert::Into::into(#final_style s.#name)
-              }
-            })
-        };
-        try_into_fields.push(quote! {
-            #src_name: #converter,
-        });
-      }
-      None => match style {
-        Style::Ref => {
-          if try_ {
-            try_into_fields.push(quote! {
-                #src_name: ::core::convert::TryInto::try_into(&s.#name)?,
-            });
-          } else {
-            try_into_fields.push(quote! {
-                #src_name: ::core::convert::Into::into(&s.#name),
-            });
-          }
-        }
-        Style::Move => {
-          if try_ {
-            try_into_fields.push(quote! {
-                #src_name: ::core::convert::TryInto::try_into(s.#name)?,
-            });
-          } else {
-            try_into_fields.push(quote! {
-                #src_name: ::core::convert::Into::into(s.#name),
-            });
-          }
-        }
-      },
-    }
-  }
+  let name = &opts.name;
+  let mut errors = try_
+    .map(|try_| Errors::new(try_, src_name, name))
+    .transpose()?;
+  let body = match &opts.variants {
+    Some(variants) => generate_into_variants(opts, variants, errors.as_mut(), style)?,
+    None => generate_into_fields(opts, errors.as_mut(), style)?,
+  };
 
-  let final_impl_generics = &final_generics.final_impl_generics;
   let self_ty_generics = &final_generics.ty_generics;
-  let final_where_clause = &final_generics.final_where_clause;
+  let final_where_clause =
+    &conversion_where_clause(opts, final_generics, style, true, errors.as_ref())?;
   let src_ty_generics = &final_generics.src_ty_generics;
+  let (style, final_impl_generics) = conversion_generics(style, &final_generics.final_generics);
 
-  let name = &opts.name;
-  if try_ {
-    let error = error.map(|t| quote!(#t)).unwrap_or_else(|| {
-      quote!(
-        ::std::boxed::Box<
-          dyn ::std::error::Error + ::core::marker::Send + ::core::marker::Sync + 'static,
-        >
-      )
-    });
+  if let Some(errors) = errors {
+    let error = errors.error_type();
+    let generated_error = errors.generate(&opts.vis, name, src_name);
     Ok(quote! {
+        #generated_error
+
         impl #final_impl_generics ::core::convert::TryFrom<#style #name #self_ty_generics> for #src_name #src_ty_generics #final_where_clause {
             type Error = #error;
 
             fn try_from(s: #style #name #self_ty_generics) -> ::core::result::Result<Self, Self::Error> {
-                ::core::result::Result::Ok(Self {
-                    #(#try_into_fields)*
-                })
+                ::core::result::Result::Ok(#body)
             }
         }
     })
@@ -128,11 +53,211 @@ pub(crate) fn generate_into(
     Ok(quote! {
         impl #final_impl_generics ::core::convert::From<#style #name #self_ty_generics> for #src_name #src_ty_generics #final_where_clause {
             fn from(s: #style #name #self_ty_generics) -> Self {
-                Self {
-                    #(#try_into_fields)*
-                }
+                #body
             }
         }
     })
   }
 }
+
+/// Returns the value of a skipped field when converting back to the source.
+fn skipped_value(skip: &FieldLevelSkip) -> proc_macro2::TokenStream {
+  match &skip.default {
+    Some(default) => quote!(#default()),
+    None => quote!(::core::default::Default::default()),
+  }
+}
+
+fn generate_into_fields(
+  opts: &StructOpts,
+  mut errors: Option<&mut Errors>,
+  style: Style,
+) -> syn::Result<proc_macro2::TokenStream> {
+  let members = opts.members()?;
+  let mut try_into_fields = vec![None; opts.fields.len()];
+  let mut stmts = Vec::new();
+  // The fields computed by `into(expr)` read the target, so they are computed before
+  // any field is moved out of it.
+  let mut computed = Vec::new();
+  let (exprs, fields): (Vec<_>, Vec<_>) = opts
+    .fields
+    .iter()
+    .enumerate()
+    .partition(|(_, (_, field))| matches!(&field.into, Some(into) if into.expr.is_some()));
+  for (idx, (src_name, field)) in exprs.into_iter().chain(fields) {
+    let src_member = src_member(src_name, field.named);
+    let cfgs = &field.cfgs;
+    let is_expr = matches!(&field.into, Some(into) if into.expr.is_some());
+    if let (Some(skip), false) = (&field.skip, is_expr) {
+      let default = skipped_value(skip);
+      try_into_fields[idx] = Some(quote! {
+          #(#cfgs)* #src_member: #default,
+      });
+      continue;
+    }
+
+    let final_style = field
+      .into
+      .as_ref()
+      .and_then(|into| into.style)
+      .unwrap_or(style);
+    // A skipped field computed by `into(expr)` is reported by its name in the source.
+    let (key, value) = match members.iter().find_map(|(name, member)| match member {
+      Member::Field(key, _) if key == src_name => Some(name),
+      _ => None,
+    }) {
+      Some(name) => (member_name(name), quote!(#final_style s.#name)),
+      None => (src_name.clone(), quote!()),
+    };
+    let report = errors
+      .as_deref_mut()
+      .map(|errors| errors.report(error_variant(&key), key));
+    let converter = field.convert_into(report.as_ref(), final_style, value)?;
+    let converter = match errors.as_deref() {
+      Some(errors) => errors.bind(&mut stmts, cfgs, converter),
+      None => converter,
+    };
+    let converter = match errors.as_deref() {
+      Some(errors) if errors.collects() => converter,
+      _ if is_expr => {
+        let local = format_ident!("__fromit_expr_{}", idx);
+        computed.push(quote!(#(#cfgs)* let #local = #converter;));
+        quote!(#local)
+      }
+      _ => converter,
+    };
+    try_into_fields[idx] = Some(quote! {
+        #(#cfgs)* #src_member: #converter,
+    });
+  }
+
+  let value = quote! {
+    Self {
+      #(#try_into_fields)*
+    }
+  };
+  // The extra fields with `into` push their values back into the converted source.
+  let pushes = members
+    .iter()
+    .filter_map(|(name, member)| match member {
+      Member::Extra(ExtraField { into: Some(f), .. }) => {
+        Some(quote!(#f(&mut src, #style s.#name);))
+      }
+      _ => None,
+    })
+    .collect::<Vec<_>>();
+  let value = if pushes.is_empty() {
+    value
+  } else {
+    quote! {
+      {
+        let mut src = #value;
+        #(#pushes)*
+        src
+      }
+    }
+  };
+  let value = match errors {
+    Some(errors) => errors.finish(stmts, value),
+    None => value,
+  };
+  Ok(if computed.is_empty() {
+    value
+  } else {
+    quote! {
+      {
+        #(#computed)*
+        #value
+      }
+    }
+  })
+}
+
+fn generate_into_variants(
+  opts: &StructOpts,
+  variants: &[Variant],
+  mut errors: Option<&mut Errors>,
+  style: Style,
+) -> syn::Result<proc_macro2::TokenStream> {
+  let name = &opts.name;
+  let mut arms = Vec::with_capacity(variants.len());
+  for variant in variants.iter().filter(|v| !v.skip) {
+    let src_variant = &variant.src_name;
+    let variant_name = variant.name();
+    let mut bindings = Vec::new();
+    let mut fields = Vec::new();
+    let mut stmts = Vec::new();
+    for (src_field, field) in variant.fields.iter() {
+      let binding = binding_ident(src_field);
+      let cfgs = &field.cfgs;
+      let value = match &field.skip {
+        Some(skip) => skipped_value(skip),
+        None => {
+          let final_style = field
+            .into
+            .as_ref()
+            .and_then(|into| into.style)
+            .unwrap_or(style);
+          if field.named {
+            let field_name = field
+              .rename
+              .clone()
+              .unwrap_or_else(|| format_ident!("{}", src_field));
+            bindings.push(quote!(#(#cfgs)* #field_name: #binding));
+          } else {
+            bindings.push(quote!(#binding));
+          }
+          let report = errors.as_deref_mut().map(|errors| {
+            let key = field
+              .rename
+              .as_ref()
+              .map(ToString::to_string)
+              .unwrap_or_else(|| src_field.clone());
+            errors.report(
+              format_ident!("{}{}", variant_name, error_variant(&key)),
+              format!("{}.{}", variant_name, key),
+            )
+          });
+          let converter = field.convert_into(
+            report.as_ref(),
+            final_style,
+            binding_access(style, final_style, &binding),
+          )?;
+          match errors.as_deref() {
+            Some(errors) => errors.bind(&mut stmts, cfgs, converter),
+            None => converter,
+          }
+        }
+      };
+      if field.named {
+        let src_field = format_ident!("{}", src_field);
+        fields.push(quote!(#(#cfgs)* #src_field: #value));
+      } else {
+        fields.push(value);
+      }
+    }
+
+    let (pattern, value) = match variant.style {
+      darling::ast::Style::Struct => (
+        quote!(#name::#variant_name { #(#bindings,)* }),
+        quote!(Self::#src_variant { #(#fields,)* }),
+      ),
+      darling::ast::Style::Tuple => (
+        quote!(#name::#variant_name(#(#bindings,)*)),
+        quote!(Self::#src_variant(#(#fields,)*)),
+      ),
+      darling::ast::Style::Unit => (quote!(#name::#variant_name), quote!(Self::#src_variant)),
+    };
+    let value = match errors.as_deref() {
+      Some(errors) => errors.finish(stmts, value),
+      None => value,
+    };
+    arms.push(quote!(#pattern => #value,));
+  }
+
+  Ok(quote! {
+    match s {
+      #(#arms)*
+    }
+  })
+}
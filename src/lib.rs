@@ -1,7 +1,5 @@
 #![doc = include_str!("../README.md")]
-#![allow(clippy::wrong_self_convention)]
-
-use std::collections::{hash_map::Entry, HashMap};
+#![allow(clippy::wrong_self_convention, clippy::manual_unwrap_or_default)]
 
 use darling::{FromDeriveInput, FromMeta, ToTokens};
 use quote::{format_ident, quote};
@@ -20,6 +18,20 @@ mod field;
 use field::*;
 mod structure;
 use structure::*;
+mod variant;
+use variant::*;
+mod builder;
+use builder::*;
+mod partial;
+use partial::*;
+mod error;
+use error::*;
+mod validate;
+use validate::*;
+mod view;
+use view::*;
+mod generics;
+use generics::*;
 
 #[derive(Default)]
 struct Attributes {
@@ -71,187 +83,364 @@ struct FinalGenerics {
   final_where_clause: Option<proc_macro2::TokenStream>,
 }
 
-fn get_final_generics(
-  this: Option<&Bound>,
-  src_generics: &syn::Generics,
-) -> syn::Result<FinalGenerics> {
-  let (src_impl_generics, src_ty_generics, src_where_clause) = src_generics.split_for_impl();
-  match this {
-    Some(b) => {
-      let self_bound = match &b.extra {
-        Some(bound) => match syn::parse_str::<syn::Generics>(&format!("<{}>", bound)) {
-          Ok(g) => g,
-          Err(e) => return Err(e),
-        },
-        None => syn::Generics::default(),
-      };
-
-      if b.inherit {
-        let mut ts = src_generics
-          .to_token_stream()
-          .to_string()
-          .trim()
-          .to_string();
-        if ts.is_empty() {
-          let (impl_generics, ty_generics, where_clause) = self_bound.split_for_impl();
-          Ok(FinalGenerics {
-            src_generics: src_generics.clone(),
-            src_impl_generics: src_impl_generics.to_token_stream(),
-            src_ty_generics: src_ty_generics.to_token_stream(),
-            src_where_clause: src_where_clause.cloned(),
-            generics: self_bound.clone(),
-            impl_generics: impl_generics.to_token_stream(),
-            ty_generics: ty_generics.to_token_stream(),
-            where_clause: where_clause.map(|w| quote!(#w)),
-            final_impl_generics: impl_generics.to_token_stream(),
-            final_ty_generics: ty_generics.to_token_stream(),
-            final_where_clause: where_clause.map(|w| quote!(#w)),
-            final_struct_generics: impl_generics.to_token_stream(),
-            final_generics: self_bound,
-          })
-        } else {
-          ts = ts.trim().trim_end_matches('>').trim().to_string();
-          if !ts.ends_with(',') {
-            ts.push(',');
-          }
-          ts.push_str(
-            self_bound
-              .to_token_stream()
-              .to_string()
-              .trim_start_matches('<'),
-          );
+impl FinalGenerics {
+  /// Creates the generics of a target, `generics` are the generics of the target and
+  /// `final_generics` are the generics of the conversions between the source and the
+  /// target.
+  fn new(
+    src_generics: &syn::Generics,
+    generics: syn::Generics,
+    final_generics: syn::Generics,
+  ) -> Self {
+    let (src_impl_generics, src_ty_generics, src_where_clause) = src_generics.split_for_impl();
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let (final_impl_generics, final_ty_generics, final_where_clause) =
+      final_generics.split_for_impl();
+    Self {
+      src_generics: src_generics.clone(),
+      src_impl_generics: src_impl_generics.to_token_stream(),
+      src_ty_generics: src_ty_generics.to_token_stream(),
+      src_where_clause: src_where_clause.cloned(),
+      impl_generics: impl_generics.to_token_stream(),
+      ty_generics: ty_generics.to_token_stream(),
+      where_clause: where_clause.map(ToTokens::to_token_stream),
+      // the definition keeps the defaults of the params
+      final_struct_generics: generics.to_token_stream(),
+      final_impl_generics: final_impl_generics.to_token_stream(),
+      final_ty_generics: final_ty_generics.to_token_stream(),
+      final_where_clause: final_where_clause.map(ToTokens::to_token_stream),
+      generics,
+      final_generics,
+    }
+  }
+}
 
-          let g = match syn::parse_str::<syn::Generics>(&ts) {
-            Ok(g) => g,
-            Err(e) => return Err(e),
-          };
-
-          let (impl_generics, ty_generics, where_cluase) = g.split_for_impl();
-          let w = match (where_cluase, src_where_clause) {
-            (None, None) => quote!(),
-            (None, Some(w)) => quote!(#w),
-            (Some(w), None) => quote!(#w),
-            (Some(sw), Some(src_w)) => {
-              let mut w = src_w.to_token_stream().to_string();
-              w.push_str(" + ");
-              w.push_str(sw.to_token_stream().to_string().trim_start_matches("where"));
-              quote!(#w)
-            }
-          };
-          Ok(FinalGenerics {
-            src_generics: src_generics.clone(),
-            src_impl_generics: src_impl_generics.to_token_stream(),
-            src_ty_generics: src_ty_generics.to_token_stream(),
-            src_where_clause: src_where_clause.cloned(),
-            generics: g.clone(),
-            impl_generics: impl_generics.to_token_stream(),
-            ty_generics: ty_generics.to_token_stream(),
-            where_clause: Some(w.clone()),
-            final_impl_generics: impl_generics.to_token_stream(),
-            final_ty_generics: ty_generics.to_token_stream(),
-            final_where_clause: Some(w),
-            final_struct_generics: impl_generics.to_token_stream(),
-            final_generics: g,
-          })
+/// Returns the name of a generic param, e.g. `T`, `'a` or `N`.
+fn param_name(param: &syn::GenericParam) -> String {
+  match param {
+    syn::GenericParam::Type(p) => p.ident.to_string(),
+    syn::GenericParam::Lifetime(p) => p.lifetime.to_string(),
+    syn::GenericParam::Const(p) => p.ident.to_string(),
+  }
+}
+
+/// Returns whether the param only declares its name, without bounds or a default.
+fn is_bare_param(param: &syn::GenericParam) -> bool {
+  match param {
+    syn::GenericParam::Type(p) => p.bounds.is_empty() && p.default.is_none() && p.attrs.is_empty(),
+    syn::GenericParam::Lifetime(p) => p.bounds.is_empty() && p.attrs.is_empty(),
+    syn::GenericParam::Const(_) => false,
+  }
+}
+
+/// Merges the params and the where predicates of `extra` into `generics`. A param
+/// declared by both must be the same, unless one of them is bare, the predicates are
+/// deduplicated. The lifetimes come first and the params with defaults last.
+fn merge_generics(
+  mut generics: syn::Generics,
+  extra: &syn::Generics,
+) -> syn::Result<syn::Generics> {
+  for param in &extra.params {
+    let name = param_name(param);
+    let existing = generics.params.iter_mut().find(|p| param_name(p) == name);
+    match existing {
+      None => generics.params.push(param.clone()),
+      Some(existing) => {
+        let same_kind =
+          std::mem::discriminant::<syn::GenericParam>(existing) == std::mem::discriminant(param);
+        if same_kind && is_bare_param(param) {
+          continue;
+        }
+        if same_kind && is_bare_param(existing) {
+          *existing = param.clone();
+          continue;
+        }
+        if existing.to_token_stream().to_string() != param.to_token_stream().to_string() {
+          return Err(syn::Error::new_spanned(
+            param,
+            format!(
+              "generic parameter `{}` is already declared with other bounds, add the bounds to the where clause instead",
+              name
+            ),
+          ));
         }
-      } else {
-        let (self_impl_generics, self_ty_generics, self_where_cluase) = self_bound.split_for_impl();
-        let i = match (
-          self_impl_generics
-            .to_token_stream()
-            .to_string()
-            .trim()
-            .is_empty(),
-          src_impl_generics
-            .to_token_stream()
-            .to_string()
-            .trim()
-            .is_empty(),
-        ) {
-          (true, true) => quote!(),
-          (true, false) => quote!(#src_impl_generics),
-          (false, true) => quote!(#self_impl_generics),
-          (false, false) => {
-            let mut ts = src_impl_generics
-              .to_token_stream()
-              .to_string()
-              .trim()
-              .trim_end_matches('>')
-              .to_string();
-            if !ts.ends_with(',') {
-              ts.push(',');
-            }
-            ts.push_str(
-              self_impl_generics
-                .to_token_stream()
-                .to_string()
-                .trim()
-                .trim_start_matches('<'),
-            );
-            match syn::parse_str::<syn::Generics>(&ts) {
-              Ok(g) => {
-                let (ig, _, _) = g.split_for_impl();
-                quote!(#ig)
-              }
-              Err(e) => return Err(e),
-            }
-          }
-        };
-        let w = match (self_where_cluase, src_where_clause) {
-          (None, None) => quote!(),
-          (None, Some(w)) => quote!(#w),
-          (Some(w), None) => quote!(#w),
-          (Some(sw), Some(src_w)) => {
-            let mut w = src_w.to_token_stream().to_string();
-            w.push_str(" + ");
-            w.push_str(sw.to_token_stream().to_string().trim_start_matches("where"));
-            quote!(#w)
-          }
-        };
-        Ok(FinalGenerics {
-          src_generics: src_generics.clone(),
-          src_impl_generics: src_impl_generics.to_token_stream(),
-          src_ty_generics: src_ty_generics.to_token_stream(),
-          src_where_clause: src_where_clause.cloned(),
-          generics: self_bound.clone(),
-          impl_generics: self_impl_generics.to_token_stream(),
-          ty_generics: self_ty_generics.to_token_stream(),
-          where_clause: self_where_cluase.map(|w| quote!(#w)),
-          final_impl_generics: i,
-          final_ty_generics: self_ty_generics.to_token_stream(),
-          final_where_clause: Some(w),
-          final_struct_generics: self_impl_generics.to_token_stream(),
-          final_generics: self_bound,
-        })
       }
     }
-    None => {
-      let (impl_generics, src_ty_generics, where_clause) = src_generics.split_for_impl();
-      Ok(FinalGenerics {
-        src_generics: src_generics.clone(),
-        src_impl_generics: src_impl_generics.to_token_stream(),
-        src_ty_generics: src_ty_generics.to_token_stream(),
-        src_where_clause: src_where_clause.cloned(),
-        generics: Default::default(),
-        impl_generics: Default::default(),
-        ty_generics: Default::default(),
-        where_clause: Default::default(),
-        final_impl_generics: impl_generics.to_token_stream(),
-        final_ty_generics: Default::default(),
-        final_where_clause: where_clause.map(|w| quote!(#w)),
-        final_generics: Default::default(),
-        final_struct_generics: Default::default(),
-      })
+  }
+
+  let mut params = std::mem::take(&mut generics.params)
+    .into_iter()
+    .collect::<Vec<_>>();
+  params.sort_by_key(|param| match param {
+    syn::GenericParam::Lifetime(_) => 0,
+    syn::GenericParam::Type(syn::TypeParam { default: None, .. })
+    | syn::GenericParam::Const(syn::ConstParam { default: None, .. }) => 1,
+    _ => 2,
+  });
+  generics.params = params.into_iter().collect();
+
+  if let Some(extra_where) = &extra.where_clause {
+    let where_clause = generics.make_where_clause();
+    for predicate in &extra_where.predicates {
+      let tokens = predicate.to_token_stream().to_string();
+      if !where_clause
+        .predicates
+        .iter()
+        .any(|p| p.to_token_stream().to_string() == tokens)
+      {
+        where_clause.predicates.push(predicate.clone());
+      }
+    }
+  }
+  Ok(generics)
+}
+
+fn get_final_generics(
+  opts: &StructOpts,
+  src_generics: &syn::Generics,
+) -> syn::Result<FinalGenerics> {
+  let Some(bound) = &opts.bound else {
+    // the target is not generic, the conversions are as generic as the source
+    return Ok(FinalGenerics::new(
+      src_generics,
+      syn::Generics::default(),
+      src_generics.clone(),
+    ));
+  };
+  let (generics, final_generics) = if bound.auto {
+    let generics = auto_generics(opts, src_generics)?;
+    let final_generics = merge_generics(src_generics.clone(), &generics)?;
+    (generics, final_generics)
+  } else {
+    let extra = bound.extra_generics()?;
+    let final_generics = merge_generics(src_generics.clone(), &extra)?;
+    match bound.inherit {
+      true => (final_generics.clone(), final_generics),
+      false => (extra, final_generics),
     }
+  };
+
+  // the substituted params are replaced in the source type of the conversions
+  let substitutions = bound.substitutions()?;
+  let mut final_generics = FinalGenerics::new(
+    src_generics,
+    substitute_generics(&generics, &substitutions)?,
+    substitute_generics(&final_generics, &substitutions)?,
+  );
+  if !substitutions.is_empty() {
+    let src_ty_generics = src_generics.split_for_impl().1.to_token_stream();
+    final_generics.src_ty_generics = substitute_tokens(src_ty_generics, &substitutions);
   }
+  Ok(final_generics)
 }
 
 struct FromIt {
   name: syn::Ident,
-  struct_opts: HashMap<String, StructOpts>,
+  struct_opts: Vec<StructOpts>,
   bound: syn::Generics,
 }
 
+fn parse_struct_opts(input: &syn::DeriveInput) -> darling::Result<Vec<StructOpts>> {
+  let mut targets: Vec<StructOpts> = Vec::new();
+  for attr in input.attrs.iter() {
+    let mut struct_name: (bool, Option<syn::Ident>) = (false, None);
+    let mut vis: (bool, Option<syn::Visibility>) = (false, None);
+    let mut bound: (bool, Option<Bound>) = (false, None);
+    let mut attributes = (false, None);
+    let mut getters = (false, None);
+    let mut setters = (false, None);
+    let mut converter = (false, None);
+    let mut extra: (bool, Option<Extra>) = (false, None);
+    let mut shape: (bool, Option<Shape>) = (false, None);
+    let mut builder: (bool, Option<StructLevelBuilder>) = (false, None);
+    let mut partial: (bool, Option<syn::Ident>) = (false, None);
+    let mut rename_all: (bool, Option<RenameAll>) = (false, None);
+    let mut selection: (bool, Option<FieldSelection>) = (false, None);
+    let mut view: (bool, Option<View>) = (false, None);
+    let mut lazy_view: (bool, Option<bool>) = (false, None);
+    let mut skip_docs: (bool, Option<bool>) = (false, None);
+    match ToString::to_string(&attr.path.clone().into_token_stream()).as_str() {
+      "fromit" => match darling::util::parse_attribute_to_meta_list(attr) {
+        Ok(data) => {
+          if data.nested.is_empty() {
+            continue;
+          }
+          let items = &data.nested;
+          for item in items {
+            match item {
+              syn::NestedMeta::Meta(inner) => {
+                let name = darling::util::path_to_string(inner.path());
+                match name.as_str() {
+                  "name" => crate::parser::Parser::parse(&name, inner, &mut struct_name)?,
+                  "extra" => crate::parser::Parser::parse(&name, inner, &mut extra)?,
+                  "attributes" => crate::parser::Parser::parse(&name, inner, &mut attributes)?,
+                  "getters" => crate::parser::Parser::parse(&name, inner, &mut getters)?,
+                  "setters" => crate::parser::Parser::parse(&name, inner, &mut setters)?,
+                  "converter" => crate::parser::Parser::parse(&name, inner, &mut converter)?,
+                  "vis" => crate::parser::Parser::parse(&name, inner, &mut vis)?,
+                  "bound" => crate::parser::Parser::parse(&name, inner, &mut bound)?,
+                  "shape" => crate::parser::Parser::parse(&name, inner, &mut shape)?,
+                  "builder" => crate::parser::Parser::parse(&name, inner, &mut builder)?,
+                  "partial" => crate::parser::Parser::parse(&name, inner, &mut partial)?,
+                  "rename_all" => crate::parser::Parser::parse(&name, inner, &mut rename_all)?,
+                  "fields" => crate::parser::Parser::parse(&name, inner, &mut selection)?,
+                  "view" => crate::parser::Parser::parse(&name, inner, &mut view)?,
+                  "lazy_view" => crate::parser::Parser::parse(&name, inner, &mut lazy_view)?,
+                  "skip_docs" => crate::parser::Parser::parse(&name, inner, &mut skip_docs)?,
+                  other => {
+                    return Err(
+                      darling::Error::unknown_field_with_alts(
+                        other,
+                        &[
+                          "name",
+                          "attributes",
+                          "getters",
+                          "setters",
+                          "converter",
+                          "vis",
+                          "bound",
+                          "extra",
+                          "shape",
+                          "builder",
+                          "partial",
+                          "rename_all",
+                          "fields",
+                          "view",
+                          "lazy_view",
+                          "skip_docs",
+                        ],
+                      )
+                      .with_span(inner),
+                    );
+                  }
+                }
+              }
+              syn::NestedMeta::Lit(inner) => {
+                return Err(darling::Error::unsupported_format("literal").with_span(inner))
+              }
+            }
+          }
+        }
+        Err(e) => {
+          return Err(e);
+        }
+      },
+      // docs, lints and the other attributes of the source
+      _ => continue,
+    }
+    if !struct_name.0 {
+      return Err(darling::Error::missing_field("name").with_span(&attr));
+    }
+    let struct_name = struct_name.1.unwrap();
+    if targets.iter().any(|opts| opts.name == struct_name) {
+      return Err(
+        darling::Error::custom(format!("duplicate target {}", struct_name)).with_span(&struct_name),
+      );
+    }
+    if let Some(partial) = &partial.1 {
+      if *partial == struct_name
+        || targets
+          .iter()
+          .any(|opts| opts.name == *partial || opts.partial.as_ref() == Some(partial))
+      {
+        return Err(
+          darling::Error::custom(format!("duplicate target {}", partial)).with_span(partial),
+        );
+      }
+    }
+
+    let style = match (&input.data, shape.1) {
+      (syn::Data::Struct(_), Some(Shape::Named)) => darling::ast::Style::Struct,
+      (syn::Data::Struct(_), Some(Shape::Tuple)) => darling::ast::Style::Tuple,
+      (syn::Data::Struct(data), None) => match (&data.fields).into() {
+        darling::ast::Style::Unit => match extra.1.as_ref().and_then(|e| e.fields.first()) {
+          Some(f) if f.named => darling::ast::Style::Struct,
+          Some(_) => darling::ast::Style::Tuple,
+          None => darling::ast::Style::Unit,
+        },
+        style => style,
+      },
+      (_, Some(_)) => {
+        return Err(darling::Error::custom("shape is only supported for struct").with_span(attr))
+      }
+      (_, None) => darling::ast::Style::Unit,
+    };
+
+    // A view is only converted from a borrowed source, its getters return the
+    // shared borrows by copy, a mutable view has no getters by default.
+    let (getters, converter) = match (&view.1, getters.1, converter.1) {
+      (Some(_), _, Some(_)) => {
+        return Err(darling::Error::custom("converter is not supported by view").with_span(attr))
+      }
+      (Some(_), _, _) if lazy_view.1.unwrap_or_default() => {
+        return Err(darling::Error::custom("lazy_view is not supported by view").with_span(attr))
+      }
+      (Some(view), getters, None) => (
+        getters.unwrap_or(StructLevelGetter {
+          style: Style::Move,
+          ignore: view.mutable,
+          ..Default::default()
+        }),
+        Converter {
+          try_from: None,
+          try_into: None,
+          from: Some(StructLevelFrom {
+            style: Style::Ref,
+            context: None,
+          }),
+          into: None,
+          infer_bounds: false,
+        },
+      ),
+      // `converter(infer_bounds)` alone keeps the default conversions.
+      (
+        None,
+        getters,
+        Some(Converter {
+          try_from: None,
+          try_into: None,
+          from: None,
+          into: None,
+          infer_bounds: true,
+        }),
+      ) => (
+        getters.unwrap_or_default(),
+        Converter {
+          infer_bounds: true,
+          ..Default::default()
+        },
+      ),
+      (None, getters, converter) => (getters.unwrap_or_default(), converter.unwrap_or_default()),
+    };
+
+    targets.push(StructOpts {
+      name: struct_name,
+      vis: vis.1.unwrap_or_else(|| input.vis.clone()),
+      bound: bound.1,
+      getters,
+      setters: setters.1.unwrap_or_default(),
+      converter,
+      attributes: attributes.1.unwrap_or_default(),
+      style,
+      builder: builder.1,
+      partial: partial.1,
+      rename_all: rename_all.1,
+      selection: selection.1,
+      view: view.1,
+      lazy_view: lazy_view.1.unwrap_or_default(),
+      docs: match skip_docs.1.unwrap_or_default() {
+        true => Vec::new(),
+        false => attrs_named(&input.attrs, "doc"),
+      },
+      skip_docs: skip_docs.1.unwrap_or_default(),
+      fields: Vec::new(),
+      variants: None,
+      extra: extra.1,
+    });
+  }
+  Ok(targets)
+}
+
 impl FromDeriveInput for FromIt {
   fn from_derive_input(input: &syn::DeriveInput) -> darling::Result<Self> {
     let struct_check = ::darling::util::ShapeSet::new(<[_]>::into_vec(Box::new([
@@ -263,271 +452,79 @@ impl FromDeriveInput for FromIt {
 
     match &input.data {
       syn::Data::Struct(data) => {
-        let mut map = HashMap::new();
-        for attr in input.attrs.iter() {
-          let mut struct_name: (bool, Option<syn::Ident>) = (false, None);
-          let mut vis: (bool, Option<syn::Visibility>) = (false, None);
-          let mut bound: (bool, Option<Bound>) = (false, None);
-          let mut attributes = (false, None);
-          let mut getters = (false, None);
-          let mut setters = (false, None);
-          let mut converter = (false, None);
-          let mut extra = (false, None);
-          #[allow(clippy::single_match)]
-          match ToString::to_string(&attr.path.clone().into_token_stream()).as_str() {
-            "fromit" => match darling::util::parse_attribute_to_meta_list(attr) {
-              Ok(data) => {
-                if data.nested.is_empty() {
-                  continue;
-                }
-                let items = &data.nested;
-                for item in items {
-                  match item {
-                    syn::NestedMeta::Meta(inner) => {
-                      let name = darling::util::path_to_string(inner.path());
-                      match name.as_str() {
-                        "name" => crate::parser::Parser::parse(&name, inner, &mut struct_name)?,
-                        "extra" => crate::parser::Parser::parse(&name, inner, &mut extra)?,
-                        "attributes" => {
-                          crate::parser::Parser::parse(&name, inner, &mut attributes)?
-                        }
-                        "getters" => crate::parser::Parser::parse(&name, inner, &mut getters)?,
-                        "setters" => crate::parser::Parser::parse(&name, inner, &mut setters)?,
-                        "converter" => crate::parser::Parser::parse(&name, inner, &mut converter)?,
-                        "vis" => crate::parser::Parser::parse(&name, inner, &mut vis)?,
-                        "bound" => crate::parser::Parser::parse(&name, inner, &mut bound)?,
-                        other => {
-                          return Err(
-                            darling::Error::unknown_field_with_alts(
-                              other,
-                              &[
-                                "name",
-                                "attributes",
-                                "getters",
-                                "setters",
-                                "converter",
-                                "vis",
-                                "bound",
-                                "extra",
-                              ],
-                            )
-                            .with_span(inner),
-                          );
-                        }
-                      }
-                    }
-                    syn::NestedMeta::Lit(inner) => {
-                      return Err(darling::Error::unsupported_format("literal").with_span(inner))
-                    }
-                  }
-                }
-              }
-              Err(e) => {
-                return Err(e);
-              }
-            },
-            _ => {}
-          }
-          if !struct_name.0 {
-            return Err(darling::Error::missing_field("name").with_span(&attr));
-          }
-          let struct_name = struct_name.1.unwrap();
-
-          map.insert(
-            struct_name.to_string(),
-            StructOpts {
-              name: struct_name,
-              vis: vis.1.unwrap_or_else(|| input.vis.clone()),
-              bound: bound.1,
-              getters: getters.1.unwrap_or_default(),
-              setters: setters.1.unwrap_or_default(),
-              converter: converter.1.unwrap_or_default(),
-              attributes: attributes.1.unwrap_or_default(),
-              fields: HashMap::new(),
-              extra: extra.1,
-            },
-          );
+        let mut targets = parse_struct_opts(input)?;
+        let names = targets
+          .iter()
+          .map(|opts| opts.name.to_string())
+          .collect::<Vec<_>>();
+        collect_fields(
+          &data.fields,
+          names
+            .iter()
+            .map(String::as_str)
+            .zip(targets.iter_mut().map(|opts| &mut opts.fields))
+            .collect(),
+        )?;
+        for opts in targets.iter_mut() {
+          opts.select_fields()?;
+          opts.substitute_fields(&input.generics)?;
+          opts.borrow_fields(&input.generics);
+          opts.rename_fields();
+          opts.strip_docs();
+          opts.add_marker(&input.generics)?;
+          opts.members()?;
         }
+        check_split(&targets)?;
 
-        for (idx, field) in data.fields.iter().enumerate() {
-          let named = field.ident.is_some();
-          for attr in &field.attrs {
-            match ToString::to_string(&attr.path.clone().into_token_stream()).as_str() {
-              "fromit" => {
-                let mut skip: (bool, Option<FieldLevelSkip>) = (false, None);
-                let mut typ: (bool, Option<syn::Type>) = (false, None);
-                let mut vis: (bool, Option<syn::Visibility>) = (false, None);
-                let mut rename: (bool, Option<syn::Ident>) = (false, None);
-                let mut parent: (bool, Option<syn::Ident>) = (false, None);
-                let mut from: (bool, Option<FieldConverter>) = (false, None);
-                let mut into: (bool, Option<FieldConverter>) = (false, None);
-                let mut attributes: (bool, Option<Attributes>) = (false, None);
-                let mut getter: (bool, Option<FieldLevelGetter>) = (false, None);
-                let mut setter: (bool, Option<FieldLevelSetter>) = (false, None);
-                match ::darling::util::parse_attribute_to_meta_list(attr) {
-                  Ok(data) => {
-                    if data.nested.is_empty() {
-                      continue;
-                    }
-                    let items = &data.nested;
-                    for item in items {
-                      match *item {
-                        syn::NestedMeta::Meta(ref inner) => {
-                          let name = ::darling::util::path_to_string(inner.path());
-                          match name.as_str() {
-                            "default" => {
-                              return Err(
-                                ::darling::Error::custom(
-                                  "default is only supported for extra fields",
-                                )
-                                .with_span(inner),
-                              )
-                            }
-                            "skip" => crate::parser::Parser::parse(&name, inner, &mut skip)?,
-                            "type" => crate::parser::Parser::parse(&name, inner, &mut typ)?,
-                            "rename" => crate::parser::Parser::parse(&name, inner, &mut rename)?,
-                            "parent" => crate::parser::Parser::parse(&name, inner, &mut parent)?,
-                            "from" => crate::parser::Parser::parse(&name, inner, &mut from)?,
-                            "into" => crate::parser::Parser::parse(&name, inner, &mut into)?,
-                            "getter" => crate::parser::Parser::parse(&name, inner, &mut getter)?,
-                            "setter" => crate::parser::Parser::parse(&name, inner, &mut setter)?,
-                            "vis" => crate::parser::Parser::parse(&name, inner, &mut vis)?,
-                            "attributes" => {
-                              crate::parser::Parser::parse(&name, inner, &mut attributes)?
-                            }
-                            other => {
-                              return Err(
-                                ::darling::Error::unknown_field_with_alts(
-                                  other,
-                                  &[
-                                    "skip",
-                                    "type",
-                                    "rename",
-                                    "parent",
-                                    "from",
-                                    "into",
-                                    "getter",
-                                    "setter",
-                                    "vis",
-                                    "attributes",
-                                  ],
-                                )
-                                .with_span(inner),
-                              );
-                            }
-                          }
-                        }
-                        syn::NestedMeta::Lit(ref inner) => {
-                          return Err(
-                            ::darling::Error::unsupported_format("literal").with_span(inner),
-                          );
-                        }
-                      }
-                    }
-                  }
-                  Err(err) => {
-                    return Err(err);
-                  }
-                }
-                if map.len() > 1 && parent.1.is_none() {
-                  return Err(::darling::Error::custom(
-                                        "parent must be specified when there are more than one struct needed to be generated",
-                                    ));
-                }
-                let f = Field {
-                  src_ty: field.ty.clone(),
-                  src_vis: field.vis.clone(),
-                  skip: skip.1,
-                  vis: vis.1,
-                  typ: typ.1,
-                  rename: rename.1,
-                  parent: parent.1,
-                  getter: getter.1.unwrap_or_default(),
-                  setter: setter.1.unwrap_or_default(),
-                  from: from.1,
-                  into: into.1,
-                  attributes: attributes.1.unwrap_or_default(),
-                  named,
-                };
-
-                let field_name = field
-                  .ident
-                  .as_ref()
-                  .map(ToString::to_string)
-                  .unwrap_or_else(|| idx.to_string());
-                if map.len() > 1 {
-                  let parent = f.parent.as_ref().unwrap().to_string();
-                  match map.get_mut(&parent) {
-                    Some(s) => match s.fields.entry(field_name) {
-                      Entry::Occupied(mut old_f) => {
-                        old_f.get_mut().merge(f);
-                      }
-                      Entry::Vacant(val) => {
-                        val.insert(f);
-                      }
-                    },
-                    None => {
-                      return Err(darling::Error::custom(format!(
-                        "Does not have parent {}",
-                        parent
-                      )))
-                    }
-                  }
-                } else {
-                  match map.iter_mut().next().unwrap().1.fields.entry(field_name) {
-                    Entry::Occupied(mut old_f) => {
-                      old_f.get_mut().merge(f);
-                    }
-                    Entry::Vacant(val) => {
-                      val.insert(f);
-                    }
-                  }
-                }
-              }
-              _ => continue,
-            }
-          }
-          let key = field
-            .ident
-            .clone()
-            .unwrap_or_else(|| format_ident!("{idx}"))
-            .to_string();
-          for (parent, s) in map.iter_mut() {
-            if !s.fields.contains_key(&key) {
-              s.fields.insert(
-                key.clone(),
-                Field {
-                  src_ty: field.ty.clone(),
-                  src_vis: field.vis.clone(),
-                  skip: None,
-                  vis: None,
-                  typ: None,
-                  rename: None,
-                  parent: Some(format_ident!("{}", parent)),
-                  getter: Default::default(),
-                  setter: Default::default(),
-                  from: None,
-                  into: None,
-                  attributes: Default::default(),
-                  named,
-                },
-              );
-            }
-          }
+        Ok(FromIt {
+          name: input.ident.clone(),
+          struct_opts: targets,
+          bound: input.generics.clone(),
+        })
+      }
+      syn::Data::Enum(data) => {
+        let mut targets = parse_struct_opts(input)?;
+        if let Some(opts) = targets.iter().find(|opts| opts.extra.is_some()) {
+          return Err(
+            darling::Error::custom("extra is not supported for enum").with_span(&opts.name),
+          );
+        }
+        if let Some(opts) = targets.iter().find(|opts| opts.view.is_some()) {
+          return Err(
+            darling::Error::custom("view is not supported for enum").with_span(&opts.name),
+          );
+        }
+        if let Some(opts) = targets.iter().find(|opts| opts.lazy_view) {
+          return Err(
+            darling::Error::custom("lazy_view is not supported for enum").with_span(&opts.name),
+          );
+        }
+        if let Some(opts) = targets.iter().find(|opts| opts.selection.is_some()) {
+          return Err(
+            darling::Error::custom("fields is not supported for enum").with_span(&opts.name),
+          );
+        }
+        if let Some(partial) = targets.iter().find_map(|opts| opts.partial.as_ref()) {
+          return Err(
+            darling::Error::custom("partial is not supported for enum").with_span(partial),
+          );
+        }
+        collect_variants(data, &mut targets)?;
+        for opts in targets.iter_mut() {
+          opts.rename_fields();
+          opts.substitute_fields(&input.generics)?;
+          opts.strip_docs();
+          opts.add_marker(&input.generics)?;
         }
 
         Ok(FromIt {
           name: input.ident.clone(),
-          struct_opts: map,
+          struct_opts: targets,
           bound: input.generics.clone(),
         })
       }
-      syn::Data::Enum(_) => Err(darling::Error::unsupported_shape_with_expected("enum", &{
-        let res = format!("struct with {}", struct_check);
-        res
-      })),
       syn::Data::Union(_) => Err(darling::Error::unsupported_shape_with_expected("union", &{
-        let res = format!("struct with {}", struct_check);
+        let res = format!("enum or struct with {}", struct_check);
         res
       })),
     }
@@ -543,15 +540,24 @@ pub fn from_it(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
   };
 
   let mut streams = Vec::new();
+  let mut split = Vec::new();
   let src_name = &fromit.name;
   let src_generics = &fromit.bound;
-  for (name, opts) in fromit.struct_opts {
-    let name = format_ident!("{}", name);
-    let final_generics = match get_final_generics(opts.bound.as_ref(), src_generics) {
+  for opts in fromit.struct_opts {
+    let name = opts.name.clone();
+    let final_generics = match get_final_generics(&opts, src_generics) {
+      Ok(g) if opts.view.is_some() => match view_generics(g) {
+        Ok(g) => g,
+        Err(e) => return e.to_compile_error().into(),
+      },
       Ok(g) => g,
       Err(e) => return e.to_compile_error().into(),
     };
-    streams.push(match generate_struct(&name, &opts, &final_generics) {
+    let generated = match &opts.variants {
+      Some(variants) => generate_enum(&name, &opts, variants, &final_generics),
+      None => generate_struct(&name, &opts, &final_generics),
+    };
+    streams.push(match generated {
       Ok(s) => s,
       Err(e) => return e.to_compile_error().into(),
     });
@@ -575,7 +581,41 @@ pub fn from_it(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
       Ok(s) => s,
       Err(e) => return e.to_compile_error().into(),
     });
+
+    streams.push(match generate_builder(&opts, &final_generics) {
+      Ok(s) => s,
+      Err(e) => return e.to_compile_error().into(),
+    });
+
+    streams.push(match generate_partial(&opts, &final_generics) {
+      Ok(s) => s,
+      Err(e) => return e.to_compile_error().into(),
+    });
+
+    streams.push(match generate_validation(&opts, &final_generics) {
+      Ok(s) => s,
+      Err(e) => return e.to_compile_error().into(),
+    });
+
+    streams.push(match generate_lazy_view(src_name, src_generics, &opts) {
+      Ok(s) => s,
+      Err(e) => return e.to_compile_error().into(),
+    });
+
+    if matches!(&opts.view, Some(view) if view.split) {
+      let ty_generics = &final_generics.ty_generics;
+      split.push(match generate_view_value(&opts) {
+        Ok(value) => (quote!(#name #ty_generics), value),
+        Err(e) => return e.to_compile_error().into(),
+      });
+    }
   }
+  streams.push(generate_split_mut(
+    src_name,
+    src_generics,
+    &input.vis,
+    &split,
+  ));
   quote! {
       #(#streams)*
   }
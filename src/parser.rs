@@ -5,25 +5,26 @@ impl Parser {
     span: &T,
     raw: &str,
   ) -> syn::Result<(bool, syn::Fields)> {
-    let Some(start) = raw.find('{') else {
-            return Err(
-                darling::Error::custom("expected left curly brace")
-                    .with_span(span),
-            )?;
-        };
-    let Some(end) = raw.rfind('}') else {
-            return Err(
-                darling::Error::custom("expected right curly brace")
-                    .with_span(span),
-            )?;
-        };
-    syn::parse_str::<syn::FieldsNamed>(&raw[start..end + 1])
-      .map(|v| (true, syn::Fields::Named(v)))
-      .or_else(|_| {
-        syn::parse_str::<syn::FieldsUnnamed>(&raw[start..end + 1])
-          .map(|v| (false, syn::Fields::Unnamed(v)))
-          .map_err(|_| syn::Error::new(span.span(), "fail to parse extra fields"))
-      })
+    let (start, close) = match raw.find(['{', '(']) {
+      Some(start) if raw[start..].starts_with('{') => (start, '}'),
+      Some(start) => (start, ')'),
+      None => {
+        return Err(
+          darling::Error::custom("expected left curly brace or left parenthesis").with_span(span),
+        )?
+      }
+    };
+    let Some(end) = raw.rfind(close) else {
+      return Err(darling::Error::custom(format!("expected `{}`", close)).with_span(span))?;
+    };
+    if close == '}' {
+      syn::parse_str::<syn::FieldsNamed>(&raw[start..end + 1])
+        .map(|v| (true, syn::Fields::Named(v)))
+    } else {
+      syn::parse_str::<syn::FieldsUnnamed>(&raw[start..end + 1])
+        .map(|v| (false, syn::Fields::Unnamed(v)))
+    }
+    .map_err(|_| syn::Error::new(span.span(), "fail to parse extra fields"))
   }
 
   pub(crate) fn parse<T>(
@@ -0,0 +1,113 @@
+use super::*;
+
+pub(crate) fn generate_partial(
+  opts: &StructOpts,
+  final_generics: &FinalGenerics,
+) -> syn::Result<proc_macro2::TokenStream> {
+  let partial = match &opts.partial {
+    Some(partial) => partial,
+    None => return Ok(quote!()),
+  };
+
+  let extra_attributes = opts
+    .extra
+    .as_ref()
+    .map(|extra| extra.attributes.attrs.as_slice())
+    .unwrap_or_default();
+  let members = opts.members()?;
+  let mut fields = Vec::with_capacity(members.len());
+  for (name, member) in members.iter() {
+    let ty = member.ty();
+    let attributes = member
+      .docs()
+      .iter()
+      .chain(member.cfgs())
+      .chain(member.attributes())
+      .chain(extra_attributes.iter());
+    let vis = member.vis().unwrap_or_else(|| member.src_vis());
+    match name {
+      syn::Member::Named(name) => fields.push(quote! {
+          #(#attributes)*
+          #vis #name: ::core::option::Option<#ty>,
+      }),
+      syn::Member::Unnamed(_) => fields.push(quote! {
+          #(#attributes)*
+          #vis ::core::option::Option<#ty>,
+      }),
+    }
+  }
+  let names = members.iter().map(|(name, _)| name).collect::<Vec<_>>();
+  let cfgs = members
+    .iter()
+    .map(|(_, member)| {
+      let cfgs = member.cfgs();
+      quote!(#(#cfgs)*)
+    })
+    .collect::<Vec<_>>();
+
+  let name = &opts.name;
+  let struct_attrs = &opts.attributes.attrs;
+  let vis = &opts.vis;
+  let final_struct_generics = &final_generics.final_struct_generics;
+  let impl_generics = &final_generics.impl_generics;
+  let ty_generics = &final_generics.ty_generics;
+  let where_clause = &final_generics.where_clause;
+  let doc = format!("A patch of [`{}`], whose fields are all optional.", name);
+  let allow = opts.allow_renamed();
+
+  let definition = match opts.style {
+    darling::ast::Style::Struct => quote! {
+      #[doc = #doc]
+      #(#struct_attrs)*
+      #allow
+      #vis struct #partial #final_struct_generics #where_clause {
+          #(#fields)*
+      }
+    },
+    darling::ast::Style::Tuple => quote! {
+      #[doc = #doc]
+      #(#struct_attrs)*
+      #vis struct #partial #final_struct_generics (
+          #(#fields)*
+      ) #where_clause;
+    },
+    darling::ast::Style::Unit => quote! {
+      #[doc = #doc]
+      #(#struct_attrs)*
+      #vis struct #partial #final_struct_generics #where_clause;
+    },
+  };
+
+  Ok(quote! {
+    #definition
+
+    impl #impl_generics #name #ty_generics #where_clause {
+      /// Overwrites the fields which are set in the patch.
+      #vis fn apply(&mut self, patch: #partial #ty_generics) {
+        #(
+          #cfgs
+          if let ::core::option::Option::Some(val) = patch.#names {
+            self.#names = val;
+          }
+        )*
+      }
+    }
+
+    impl #impl_generics #partial #ty_generics #where_clause {
+      /// Merges two patches, the fields set in `other` win.
+      #vis fn merge(self, other: Self) -> Self {
+        Self {
+          #(#cfgs #names: other.#names.or(self.#names),)*
+        }
+      }
+    }
+
+    impl #impl_generics ::core::convert::From<#name #ty_generics> for #partial #ty_generics #where_clause {
+      fn from(s: #name #ty_generics) -> Self {
+        Self {
+          #(#cfgs #names: ::core::option::Option::Some(s.#names),)*
+        }
+      }
+    }
+  })
+}
@@ -2,13 +2,13 @@ use super::*;
 
 #[derive(Default, FromMeta)]
 pub(crate) struct FieldLevelSetter {
-  rename: Option<syn::Ident>,
-  style: Option<SetterStyle>,
+  pub(crate) rename: Option<syn::Ident>,
+  pub(crate) style: Option<SetterStyle>,
   #[darling(default, rename = "skip")]
-  ignore: bool,
-  vis: Option<syn::Visibility>,
+  pub(crate) ignore: bool,
+  pub(crate) vis: Option<syn::Visibility>,
   #[darling(default)]
-  bound: FieldLevelBound,
+  pub(crate) bound: FieldLevelBound,
 }
 
 #[derive(FromMeta)]
@@ -43,13 +43,16 @@ pub(crate) enum SetterStyle {
 }
 
 impl SetterStyle {
+  /// Returns the setter of the field, a `try_into` setter of a field with `validate`
+  /// also checks the value, and returns the given validation error.
   pub(crate) fn to_setter(
     &self,
     fn_vis: &syn::Visibility,
     bound: Option<&syn::Generics>,
-    field_name: &syn::Ident,
+    field_name: &syn::Member,
     field_ty: &syn::Type,
     fn_name: &syn::Ident,
+    validation: Option<(&syn::Ident, &Validate)>,
   ) -> proc_macro2::TokenStream {
     match self {
       Self::Ref => quote! {
@@ -70,13 +73,26 @@ impl SetterStyle {
         }
       },
       Self::TryInto => {
-        let bound = bound.map(|tt| {
-          let bound = format!(
-            "{}, Error>",
-            tt.to_token_stream().to_string().trim_end_matches('>')
-          );
-          syn::parse_str::<syn::Generics>(&bound).unwrap()
-        });
+        let bound = try_into_generics(bound);
+        if let Some((error, validate)) = validation {
+          let field = member_name(field_name);
+          let check = validate.check(error, &field, quote!(&val));
+          return quote! {
+            #fn_vis fn #fn_name #bound (mut self, val: impl ::core::convert::TryInto<#field_ty, Error = Error>) -> ::core::result::Result<Self, #error>
+            where
+              Error: ::std::error::Error + ::core::marker::Send + ::core::marker::Sync + 'static,
+            {
+              let val = ::core::convert::TryInto::try_into(val).map_err(|e| #error {
+                field: #field,
+                rule: "try_into",
+                source: ::core::option::Option::Some(::std::boxed::Box::new(e)),
+              })?;
+              #check?;
+              self.#field_name = val;
+              ::core::result::Result::Ok(self)
+            }
+          };
+        }
         quote! {
           #fn_vis fn #fn_name #bound (mut self, val: impl ::core::convert::TryInto<#field_ty, Error = Error>) -> ::core::result::Result<Self, Error> {
             self.#field_name = ::core::convert::TryInto::try_into(val)?;
@@ -88,107 +104,73 @@ impl SetterStyle {
   }
 }
 
+/// Returns the generics of a `try_into` setter, which are the `bound` of the setter
+/// followed by the `Error` of the conversion.
+pub(crate) fn try_into_generics(bound: Option<&syn::Generics>) -> syn::Generics {
+  let mut generics = bound.cloned().unwrap_or_default();
+  generics.params.push(syn::parse_quote!(Error));
+  generics
+}
+
 pub(crate) fn generate_setters(
   opts: &StructOpts,
   final_generics: &FinalGenerics,
 ) -> syn::Result<proc_macro2::TokenStream> {
-  if opts.setters.ignore {
+  if opts.setters.ignore || opts.variants.is_some() {
     return Ok(quote!());
   }
   let mut setters = Vec::new();
-  let mut extra_setters = Vec::new();
   let setters_prefix = opts
     .setters
     .prefix
     .as_ref()
     .cloned()
     .unwrap_or_else(|| format_ident!("set"));
+  let validation_error = validation_error_name(&opts.name);
 
-  let mut ctr = 0;
-  if let Some(extra) = &opts.extra {
-    for field in extra.fields.values() {
-      if field.setter.ignore {
-        continue;
-      }
-
-      let src_name = field
-        .name
-        .clone()
-        .unwrap_or_else(|| format_ident!("{}", ctr));
-      ctr += usize::from(field.named);
-
-      let field_name = field.name.as_ref().unwrap_or(&src_name);
-      let vis = field.setter.vis.as_ref().unwrap_or_else(|| {
-        opts
-          .setters
-          .vis_all
-          .as_ref()
-          .unwrap_or_else(|| field.vis.as_ref().unwrap_or(&opts.vis))
-      });
-      let fn_name = field
-        .setter
-        .rename
-        .clone()
-        .unwrap_or_else(|| format_ident!("{}_{}", setters_prefix, field_name));
-
-      let field_ty = &field.src_ty;
-      extra_setters.push(field.setter.style.unwrap_or(opts.setters.style).to_setter(
-        vis,
-        field.setter.bound.bound.as_ref(),
-        field_name,
-        field_ty,
-        &fn_name,
-      ));
-    }
-  }
-
-  for (src_name, field) in opts.fields.iter() {
-    if field.skip.is_some() || field.setter.ignore {
+  for (field_name, member) in opts.members()? {
+    let setter = member.setter();
+    if setter.ignore {
       continue;
     }
-    let src_name = field.rename.clone().unwrap_or_else(|| {
-      if field.named {
-        format_ident!("{}", src_name)
-      } else {
-        format_ident!("{}", ctr)
-      }
-    });
-    ctr += usize::from(field.named);
 
-    let field_name = field.rename.as_ref().unwrap_or(&src_name);
-    let vis = field.getter.vis.as_ref().unwrap_or_else(|| {
+    let vis = setter.vis.as_ref().unwrap_or_else(|| {
       opts
-        .getters
+        .setters
         .vis_all
         .as_ref()
-        .unwrap_or_else(|| field.vis.as_ref().unwrap_or(&opts.vis))
+        .unwrap_or_else(|| member.vis().unwrap_or(&opts.vis))
     });
-    let fn_name = field
-      .setter
+    let fn_name = setter
       .rename
       .clone()
-      .unwrap_or_else(|| format_ident!("{}_{}", setters_prefix, field_name));
-
-    let field_ty = field.typ.as_ref().unwrap_or(&field.src_ty);
-    setters.push(field.setter.style.unwrap_or(opts.setters.style).to_setter(
+      .unwrap_or_else(|| format_ident!("{}_{}", setters_prefix, member_name(&field_name)));
+
+    let validate = match member {
+      Member::Field(_, field) => field.validate.as_ref(),
+      Member::Extra(_) => None,
+    };
+    let cfgs = member.cfgs();
+    let setter = setter.style.unwrap_or(opts.setters.style).to_setter(
       vis,
-      field.setter.bound.bound.as_ref(),
-      field_name,
-      field_ty,
+      setter.bound.bound.as_ref(),
+      &field_name,
+      member.ty(),
       &fn_name,
-    ));
+      validate.map(|validate| (&validation_error, validate)),
+    );
+    setters.push(quote!(#(#cfgs)* #setter));
   }
 
   let name = &opts.name;
   let impl_generics = &final_generics.impl_generics;
   let self_ty_generics = &final_generics.ty_generics;
   let where_clause = &final_generics.where_clause;
+  let allow = opts.allow_renamed();
   Ok(quote! {
+      #allow
       impl #impl_generics #name #self_ty_generics #where_clause {
-
           #(#setters)*
-
-          #(#extra_setters)*
       }
   })
 }
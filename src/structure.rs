@@ -4,15 +4,74 @@ use super::*;
 pub(crate) struct Try {
   #[darling(default)]
   pub(crate) style: Style,
-  pub(crate) error: Option<syn::Type>,
+  pub(crate) error: Option<TryError>,
+  /// Converts every field and returns all the errors.
+  #[darling(default)]
+  pub(crate) collect_errors: bool,
+  /// The type of the context passed to `try_from_with`.
+  pub(crate) context: Option<syn::Type>,
+}
+
+/// The `from` of the converter, which is a style, e.g. `from = "ref"`, or a list,
+/// e.g. `from(style = "ref", context = "MyCtx")`.
+#[derive(Default, Clone)]
+pub(crate) struct StructLevelFrom {
+  pub(crate) style: Style,
+  /// The type of the context passed to `from_with`.
+  pub(crate) context: Option<syn::Type>,
+}
+
+impl FromMeta for StructLevelFrom {
+  fn from_word() -> darling::Result<Self> {
+    Ok(Self::default())
+  }
+
+  fn from_string(value: &str) -> darling::Result<Self> {
+    Style::from_string(value).map(|style| Self {
+      style,
+      context: None,
+    })
+  }
+
+  fn from_list(items: &[syn::NestedMeta]) -> darling::Result<Self> {
+    let mut style: (bool, Option<Style>) = (false, None);
+    let mut context: (bool, Option<syn::Type>) = (false, None);
+    for item in items {
+      match item {
+        syn::NestedMeta::Meta(inner) => {
+          let name = darling::util::path_to_string(inner.path());
+          match name.as_str() {
+            "style" => crate::parser::Parser::parse(&name, inner, &mut style)?,
+            "context" => crate::parser::Parser::parse(&name, inner, &mut context)?,
+            other => {
+              return Err(
+                darling::Error::unknown_field_with_alts(other, &["style", "context"])
+                  .with_span(inner),
+              );
+            }
+          }
+        }
+        syn::NestedMeta::Lit(inner) => {
+          return Err(darling::Error::unsupported_format("literal").with_span(inner));
+        }
+      }
+    }
+    Ok(Self {
+      style: style.1.unwrap_or_default(),
+      context: context.1,
+    })
+  }
 }
 
 #[derive(FromMeta)]
 pub(crate) struct Converter {
   pub(crate) try_from: Option<Try>,
   pub(crate) try_into: Option<Try>,
-  pub(crate) from: Option<Style>,
+  pub(crate) from: Option<StructLevelFrom>,
   pub(crate) into: Option<Style>,
+  /// Adds the bounds of the field conversions to the where clauses of the impls.
+  #[darling(default)]
+  pub(crate) infer_bounds: bool,
 }
 
 impl Default for Converter {
@@ -20,21 +79,70 @@ impl Default for Converter {
     Self {
       try_from: None,
       try_into: None,
-      from: Some(Style::default()),
+      from: Some(StructLevelFrom::default()),
       into: Some(Style::default()),
+      infer_bounds: false,
     }
   }
 }
 
 pub(crate) struct Bound {
   pub(crate) inherit: bool,
-  pub(crate) extra: Option<String>,
+  /// Keeps the params of the source which are referenced by the kept fields.
+  pub(crate) auto: bool,
+  pub(crate) extra: Option<syn::LitStr>,
+  /// Replaces params of the source with types, e.g. `T = String`.
+  pub(crate) substitute: Option<syn::LitStr>,
+}
+
+impl Bound {
+  /// Parses the substitutions of `substitute`, e.g. `T = String, U = Vec<u8>`, spanned
+  /// by the literal.
+  pub(crate) fn substitutions(&self) -> syn::Result<Vec<(syn::Ident, syn::Type)>> {
+    let Some(substitute) = &self.substitute else {
+      return Ok(Vec::new());
+    };
+    substitute.parse_with(|input: syn::parse::ParseStream| {
+      let mut substitutions = Vec::new();
+      while !input.is_empty() {
+        let param = input.parse::<syn::Ident>()?;
+        input.parse::<syn::Token![=]>()?;
+        substitutions.push((param, input.parse::<syn::Type>()?));
+        if !input.is_empty() {
+          input.parse::<syn::Token![,]>()?;
+        }
+      }
+      Ok(substitutions)
+    })
+  }
+
+  /// Parses the generics of `extra`, e.g. `T: Clone, U where U: Default`, spanned by
+  /// the literal.
+  pub(crate) fn extra_generics(&self) -> syn::Result<syn::Generics> {
+    let Some(extra) = &self.extra else {
+      return Ok(syn::Generics::default());
+    };
+    extra.parse_with(|input: syn::parse::ParseStream| {
+      let mut generics = syn::Generics::default();
+      while !input.is_empty() && !input.peek(syn::Token![where]) {
+        generics.params.push(input.parse()?);
+        if input.is_empty() || input.peek(syn::Token![where]) {
+          break;
+        }
+        input.parse::<syn::Token![,]>()?;
+      }
+      generics.where_clause = input.parse()?;
+      Ok(generics)
+    })
+  }
 }
 
 impl FromMeta for Bound {
   fn from_list(items: &[syn::NestedMeta]) -> ::darling::Result<Self> {
     let mut inherit: (bool, Option<bool>) = (false, None);
-    let mut extra: (bool, Option<String>) = (false, None);
+    let mut auto: (bool, Option<bool>) = (false, None);
+    let mut extra: (bool, Option<syn::LitStr>) = (false, None);
+    let mut substitute: (bool, Option<syn::LitStr>) = (false, None);
     let mut errors = ::darling::Error::accumulator();
     for item in items {
       match *item {
@@ -56,6 +164,21 @@ impl FromMeta for Bound {
                 errors.push(::darling::Error::duplicate_field("inherit").with_span(&inner));
               }
             }
+            "auto" => {
+              if !auto.0 {
+                match inner {
+                  syn::Meta::Path(_) => auto = (true, Some(true)),
+                  syn::Meta::List(_) => {
+                    return Err(::darling::Error::unsupported_format("list").with_span(inner))
+                  }
+                  syn::Meta::NameValue(v) => {
+                    auto = (true, Some(<bool as darling::FromMeta>::from_value(&v.lit)?));
+                  }
+                }
+              } else {
+                errors.push(::darling::Error::duplicate_field("auto").with_span(&inner));
+              }
+            }
             "extra" => {
               if !extra.0 {
                 extra = (
@@ -69,10 +192,26 @@ impl FromMeta for Bound {
                 errors.push(::darling::Error::duplicate_field("extra").with_span(&inner));
               }
             }
+            "substitute" => {
+              if !substitute.0 {
+                substitute = (
+                  true,
+                  errors.handle(
+                    ::darling::FromMeta::from_meta(inner)
+                      .map_err(|e| e.with_span(&inner).at("substitute")),
+                  ),
+                );
+              } else {
+                errors.push(::darling::Error::duplicate_field("substitute").with_span(&inner));
+              }
+            }
             other => {
               errors.push(
-                ::darling::Error::unknown_field_with_alts(other, &["inherit", "extra"])
-                  .with_span(inner),
+                ::darling::Error::unknown_field_with_alts(
+                  other,
+                  &["inherit", "auto", "extra", "substitute"],
+                )
+                .with_span(inner),
               );
             }
           }
@@ -84,16 +223,25 @@ impl FromMeta for Bound {
     }
 
     errors.finish()?;
+    let inherit = inherit.1.unwrap_or(false);
+    let auto = auto.1.unwrap_or(false);
+    if inherit && auto {
+      return Err(darling::Error::custom(
+        "inherit and auto cannot be specified at the same time",
+      ));
+    }
     Ok(Self {
-      inherit: inherit.1.unwrap_or(false),
+      inherit,
+      auto,
       extra: extra.1,
+      substitute: substitute.1,
     })
   }
 }
 
 pub(crate) struct Extra {
   pub(crate) attributes: Attributes,
-  pub(crate) fields: HashMap<String, ExtraField>,
+  pub(crate) fields: Vec<ExtraField>,
 }
 
 impl darling::FromMeta for Extra {
@@ -155,24 +303,25 @@ impl darling::FromMeta for Extra {
     Ok(Self {
       attributes: attributes.1.unwrap_or_default(),
       fields: if !extra_fields.0 {
-        HashMap::new()
+        Vec::new()
       } else {
-        let mut fields = HashMap::new();
+        let mut fields = Vec::new();
         let (named, extra_fields) = extra_fields.1.unwrap();
-        for (idx, field) in extra_fields.into_iter().enumerate() {
-          let key = field
-            .ident
-            .clone()
-            .unwrap_or_else(|| format_ident!("{idx}"))
-            .to_string();
+        for field in extra_fields.into_iter() {
+          let mut extra_field = None;
           for attr in &field.attrs {
             match ToString::to_string(&attr.path.clone().into_token_stream()).as_str() {
               "fromit" => {
                 let mut vis: (bool, Option<syn::Visibility>) = (false, None);
                 let mut default: (bool, Option<syn::Path>) = (false, None);
+                let mut from: (bool, Option<ExtraFrom>) = (false, None);
+                let mut into: (bool, Option<syn::Path>) = (false, None);
                 let mut attributes: (bool, Option<Attributes>) = (false, None);
                 let mut getter: (bool, Option<FieldLevelGetter>) = (false, None);
                 let mut setter: (bool, Option<FieldLevelSetter>) = (false, None);
+                let mut position: (bool, Option<usize>) = (false, None);
+                let mut before: (bool, Option<syn::Ident>) = (false, None);
+                let mut after: (bool, Option<syn::Ident>) = (false, None);
                 match ::darling::util::parse_attribute_to_meta_list(attr) {
                   Ok(data) => {
                     if data.nested.is_empty() {
@@ -191,6 +340,11 @@ impl darling::FromMeta for Extra {
                             "attributes" => {
                               crate::parser::Parser::parse(&name, inner, &mut attributes)?
                             }
+                            "position" => {
+                              crate::parser::Parser::parse(&name, inner, &mut position)?
+                            }
+                            "before" => crate::parser::Parser::parse(&name, inner, &mut before)?,
+                            "after" => crate::parser::Parser::parse(&name, inner, &mut after)?,
                             "skip" => {
                               return Err(
                                 ::darling::Error::custom("skip is not supported for extra field")
@@ -215,23 +369,24 @@ impl darling::FromMeta for Extra {
                                   .with_span(inner),
                               )
                             }
-                            "from" => {
-                              return Err(
-                                ::darling::Error::custom("from is not supported for extra field")
-                                  .with_span(inner),
-                              )
-                            }
-                            "into" => {
-                              return Err(
-                                ::darling::Error::custom("into is not supported for extra field")
-                                  .with_span(inner),
-                              )
-                            }
+                            "from" => crate::parser::Parser::parse(&name, inner, &mut from)?,
+                            "into" => crate::parser::Parser::parse(&name, inner, &mut into)?,
                             other => {
                               return Err(
                                 ::darling::Error::unknown_field_with_alts(
                                   other,
-                                  &["default", "getter", "setter", "vis", "attributes"],
+                                  &[
+                                    "default",
+                                    "from",
+                                    "into",
+                                    "getter",
+                                    "setter",
+                                    "vis",
+                                    "attributes",
+                                    "position",
+                                    "before",
+                                    "after",
+                                  ],
                                 )
                                 .with_span(inner),
                               );
@@ -251,7 +406,28 @@ impl darling::FromMeta for Extra {
                   }
                 }
 
-                let f = ExtraField {
+                if from.0 && default.0 {
+                  return Err(
+                    darling::Error::custom("default and from cannot be specified at the same time")
+                      .with_span(attr),
+                  );
+                }
+
+                let placement = match (position.1, before.1, after.1) {
+                  (None, None, None) => None,
+                  (Some(position), None, None) => Some(Placement::Position(position)),
+                  (None, Some(before), None) => Some(Placement::Before(before)),
+                  (None, None, Some(after)) => Some(Placement::After(after)),
+                  _ => {
+                    return Err(
+                      darling::Error::custom(
+                        "only one of `position`, `before` and `after` can be specified",
+                      )
+                      .with_span(attr),
+                    )
+                  }
+                };
+                extra_field = Some(ExtraField {
                   src_ty: field.ty.clone(),
                   src_vis: field.vis.clone(),
                   vis: vis.1,
@@ -259,28 +435,31 @@ impl darling::FromMeta for Extra {
                   setter: setter.1.unwrap_or_default(),
                   attributes: attributes.1.unwrap_or_default(),
                   default: default.1,
+                  from: from.1,
+                  into: into.1,
+                  placement,
                   named,
                   name: field.ident.clone(),
-                };
-                fields.insert(key.clone(), f);
+                });
               }
               _ => continue,
             }
           }
 
-          if let std::collections::hash_map::Entry::Vacant(e) = fields.entry(key) {
-            e.insert(ExtraField {
-              name: field.ident.clone(),
-              src_ty: field.ty.clone(),
-              src_vis: field.vis.clone(),
-              vis: None,
-              getter: FieldLevelGetter::default(),
-              setter: FieldLevelSetter::default(),
-              default: None,
-              attributes: Attributes::default(),
-              named,
-            });
-          }
+          fields.push(extra_field.unwrap_or_else(|| ExtraField {
+            name: field.ident.clone(),
+            src_ty: field.ty.clone(),
+            src_vis: field.vis.clone(),
+            vis: None,
+            getter: FieldLevelGetter::default(),
+            setter: FieldLevelSetter::default(),
+            default: None,
+            from: None,
+            into: None,
+            placement: None,
+            attributes: Attributes::default(),
+            named,
+          }));
         }
         fields
       },
@@ -288,6 +467,123 @@ impl darling::FromMeta for Extra {
   }
 }
 
+/// The shape of the generated struct, `shape = "named"` or `shape = "tuple"`.
+#[derive(FromMeta, Clone, Copy)]
+pub(crate) enum Shape {
+  Named,
+  Tuple,
+}
+
+/// A list of field names in a string literal, e.g. `"a, b, c"`.
+pub(crate) struct FieldNames {
+  names: Vec<String>,
+  span: proc_macro2::Span,
+}
+
+impl FromMeta for FieldNames {
+  fn from_value(value: &syn::Lit) -> darling::Result<Self> {
+    match value {
+      syn::Lit::Str(s) => Ok(Self {
+        names: s
+          .value()
+          .split(',')
+          .map(str::trim)
+          .filter(|name| !name.is_empty())
+          .map(ToString::to_string)
+          .collect(),
+        span: s.span(),
+      }),
+      lit => Err(darling::Error::unexpected_lit_type(lit)),
+    }
+  }
+}
+
+/// Which fields of the source are inherited, `fields(only = "a, b")`,
+/// `fields(exclude = "c")`, or `fields(default_skip)` which only inherits the
+/// fields with a `fromit` attribute for the target.
+#[derive(FromMeta)]
+pub(crate) struct FieldSelection {
+  only: Option<FieldNames>,
+  exclude: Option<FieldNames>,
+  #[darling(default)]
+  default_skip: bool,
+}
+
+/// How the fields of the generated struct are renamed from the source, e.g.
+/// `rename_all = "camelCase"` or `rename_all = "prefix:db_"`.
+pub(crate) enum RenameAll {
+  Lower,
+  Upper,
+  Pascal,
+  Camel,
+  Snake,
+  ScreamingSnake,
+  Prefix(String),
+  Suffix(String),
+}
+
+impl FromMeta for RenameAll {
+  fn from_string(value: &str) -> darling::Result<Self> {
+    if let Some(prefix) = value.strip_prefix("prefix:") {
+      return Ok(Self::Prefix(prefix.to_string()));
+    }
+    if let Some(suffix) = value.strip_prefix("suffix:") {
+      return Ok(Self::Suffix(suffix.to_string()));
+    }
+    Ok(match value {
+      "lowercase" => Self::Lower,
+      "UPPERCASE" => Self::Upper,
+      "PascalCase" => Self::Pascal,
+      "camelCase" => Self::Camel,
+      "snake_case" => Self::Snake,
+      "SCREAMING_SNAKE_CASE" => Self::ScreamingSnake,
+      other => {
+        return Err(darling::Error::custom(format!(
+          "unknown rename_all `{}`, expected one of lowercase, UPPERCASE, PascalCase, \
+           camelCase, snake_case, SCREAMING_SNAKE_CASE, prefix:... or suffix:...",
+          other
+        )))
+      }
+    })
+  }
+}
+
+impl RenameAll {
+  /// Returns the new name of the field named `name` in the source.
+  pub(crate) fn apply(&self, name: &str) -> syn::Ident {
+    let name = name.trim_start_matches("r#");
+    let words = || name.split('_').filter(|word| !word.is_empty());
+    let capitalize = |word: &str| {
+      let mut chars = word.chars();
+      match chars.next() {
+        Some(first) => first
+          .to_uppercase()
+          .chain(chars.flat_map(char::to_lowercase))
+          .collect(),
+        None => String::new(),
+      }
+    };
+    let renamed = match self {
+      Self::Lower => name.to_lowercase(),
+      Self::Upper => name.to_uppercase(),
+      Self::Pascal => words().map(capitalize).collect(),
+      Self::Camel => words()
+        .enumerate()
+        .map(|(idx, word)| match idx {
+          0 => word.to_lowercase(),
+          _ => capitalize(word),
+        })
+        .collect(),
+      Self::Snake => name.to_lowercase(),
+      Self::ScreamingSnake => name.to_uppercase(),
+      Self::Prefix(prefix) => format!("{}{}", prefix, name),
+      Self::Suffix(suffix) => format!("{}{}", name, suffix),
+    };
+    syn::parse_str(&renamed)
+      .unwrap_or_else(|_| syn::Ident::new_raw(&renamed, proc_macro2::Span::call_site()))
+  }
+}
+
 pub(crate) struct StructOpts {
   pub(crate) name: syn::Ident,
   pub(crate) vis: syn::Visibility,
@@ -296,69 +592,408 @@ pub(crate) struct StructOpts {
   pub(crate) setters: StructLevelSetter,
   pub(crate) converter: Converter,
   pub(crate) attributes: Attributes,
-  pub(crate) fields: HashMap<String, Field>,
+  pub(crate) style: darling::ast::Style,
+  pub(crate) builder: Option<StructLevelBuilder>,
+  pub(crate) partial: Option<syn::Ident>,
+  pub(crate) rename_all: Option<RenameAll>,
+  pub(crate) selection: Option<FieldSelection>,
+  pub(crate) view: Option<View>,
+  pub(crate) lazy_view: bool,
+  /// The doc comments of the source, which are not copied with `skip_docs`.
+  pub(crate) docs: Vec<syn::Attribute>,
+  pub(crate) skip_docs: bool,
+  pub(crate) fields: Vec<(String, Field)>,
+  pub(crate) variants: Option<Vec<Variant>>,
   pub(crate) extra: Option<Extra>,
 }
 
+/// A member of the generated struct.
+pub(crate) enum Member<'a> {
+  Extra(&'a ExtraField),
+  Field(&'a str, &'a Field),
+}
+
+impl Member<'_> {
+  /// Returns the name of the field in the generated struct, `None` for a tuple field
+  /// which is not renamed.
+  pub(crate) fn ident(&self) -> Option<syn::Ident> {
+    match self {
+      Member::Extra(f) => f.name.clone(),
+      Member::Field(src_name, f) => f
+        .rename
+        .clone()
+        .or_else(|| f.named.then(|| format_ident!("{}", src_name))),
+    }
+  }
+
+  /// Returns the type of the field in the generated struct.
+  pub(crate) fn ty(&self) -> &syn::Type {
+    match self {
+      Member::Extra(f) => &f.src_ty,
+      Member::Field(_, f) => f.typ.as_ref().unwrap_or(&f.src_ty),
+    }
+  }
+
+  pub(crate) fn src_vis(&self) -> &syn::Visibility {
+    match self {
+      Member::Extra(f) => &f.src_vis,
+      Member::Field(_, f) => &f.src_vis,
+    }
+  }
+
+  pub(crate) fn vis(&self) -> Option<&syn::Visibility> {
+    match self {
+      Member::Extra(f) => f.vis.as_ref(),
+      Member::Field(_, f) => f.vis.as_ref(),
+    }
+  }
+
+  pub(crate) fn attributes(&self) -> &[syn::Attribute] {
+    match self {
+      Member::Extra(f) => &f.attributes.attrs,
+      Member::Field(_, f) => &f.attributes.attrs,
+    }
+  }
+
+  /// Returns the doc comments of the source field, which are empty for an extra field.
+  pub(crate) fn docs(&self) -> &[syn::Attribute] {
+    match self {
+      Member::Extra(_) => &[],
+      Member::Field(_, f) => &f.docs,
+    }
+  }
+
+  /// Returns the `#[cfg(...)]` attributes of the source field, which are empty for an
+  /// extra field.
+  pub(crate) fn cfgs(&self) -> &[syn::Attribute] {
+    match self {
+      Member::Extra(_) => &[],
+      Member::Field(_, f) => &f.cfgs,
+    }
+  }
+
+  pub(crate) fn getter(&self) -> &FieldLevelGetter {
+    match self {
+      Member::Extra(f) => &f.getter,
+      Member::Field(_, f) => &f.getter,
+    }
+  }
+
+  pub(crate) fn setter(&self) -> &FieldLevelSetter {
+    match self {
+      Member::Extra(f) => &f.setter,
+      Member::Field(_, f) => &f.setter,
+    }
+  }
+}
+
+impl StructOpts {
+  /// Removes the doc comments of the fields and the variants with `skip_docs`.
+  pub(crate) fn strip_docs(&mut self) {
+    if !self.skip_docs {
+      return;
+    }
+    for (_, field) in self.fields.iter_mut() {
+      field.docs.clear();
+    }
+    for variant in self.variants.iter_mut().flatten() {
+      variant.docs.clear();
+      for (_, field) in variant.fields.iter_mut() {
+        field.docs.clear();
+      }
+    }
+  }
+
+  /// Skips the fields which are not selected by `fields(...)`.
+  pub(crate) fn select_fields(&mut self) -> darling::Result<()> {
+    let Some(FieldSelection {
+      only,
+      exclude,
+      default_skip,
+    }) = &self.selection
+    else {
+      return Ok(());
+    };
+    if only.is_some() && exclude.is_some() {
+      return Err(
+        darling::Error::custom("only and exclude cannot be specified at the same time")
+          .with_span(&self.name),
+      );
+    }
+    for names in only.iter().chain(exclude) {
+      if let Some(name) = names
+        .names
+        .iter()
+        .find(|name| !self.fields.iter().any(|(key, _)| key == *name))
+      {
+        return Err(
+          darling::Error::custom(format!("the source does not have field `{}`", name))
+            .with_span(&names.span),
+        );
+      }
+    }
+
+    for (key, field) in self.fields.iter_mut() {
+      let selected = match (only, exclude) {
+        (Some(only), _) => only.names.contains(key),
+        (_, Some(exclude)) => !exclude.names.contains(key),
+        (None, None) => true,
+      };
+      if field.skip.is_none() && (!selected || (*default_skip && field.implicit)) {
+        field.skip = Some(FieldLevelSkip { default: None });
+      }
+    }
+    Ok(())
+  }
+
+  /// Renames the named fields which are not renamed by `rename`, per `rename_all`.
+  pub(crate) fn rename_fields(&mut self) {
+    let Some(rename_all) = &self.rename_all else {
+      return;
+    };
+    // The fields of a tuple struct cannot be renamed.
+    let fields = match self.style {
+      darling::ast::Style::Tuple => None,
+      _ => Some(self.fields.iter_mut()),
+    };
+    let variant_fields = self
+      .variants
+      .iter_mut()
+      .flatten()
+      .flat_map(|v| v.fields.iter_mut());
+    for (src_name, field) in fields.into_iter().flatten().chain(variant_fields) {
+      if field.named && field.rename.is_none() {
+        field.rename = Some(rename_all.apply(src_name));
+      }
+    }
+  }
+
+  /// Returns the `allow` of the lints on the names of the fields, which may not be
+  /// snake case when they are renamed by `rename_all`.
+  pub(crate) fn allow_renamed(&self) -> Option<proc_macro2::TokenStream> {
+    self
+      .rename_all
+      .as_ref()
+      .map(|_| quote!(#[allow(non_snake_case)]))
+  }
+
+  /// Returns the members of the generated struct in their final order, paired with
+  /// the name or the index used to access them, skipped fields are not included.
+  ///
+  /// The extra fields without placement come first, then the inherited fields in
+  /// declaration order, the placed extra fields are inserted at last.
+  pub(crate) fn members(&self) -> syn::Result<Vec<(syn::Member, Member<'_>)>> {
+    let extra = self
+      .extra
+      .as_ref()
+      .map(|e| e.fields.as_slice())
+      .unwrap_or_default();
+    let mut members = extra
+      .iter()
+      .filter(|f| f.placement.is_none())
+      .map(Member::Extra)
+      .chain(
+        self
+          .fields
+          .iter()
+          .filter(|(_, f)| f.skip.is_none())
+          .map(|(src_name, f)| Member::Field(src_name, f)),
+      )
+      .collect::<Vec<_>>();
+
+    for field in extra {
+      let idx = match &field.placement {
+        None => continue,
+        Some(Placement::Position(position)) => {
+          if *position > members.len() {
+            return Err(syn::Error::new_spanned(
+              &self.name,
+              format!(
+                "position {} is out of range, `{}` only has {} fields",
+                position,
+                self.name,
+                members.len()
+              ),
+            ));
+          }
+          *position
+        }
+        Some(Placement::Before(anchor)) | Some(Placement::After(anchor)) => {
+          let anchor_name = anchor.to_string();
+          let idx = members
+            .iter()
+            .position(|m| matches!(m.ident(), Some(name) if name == anchor_name))
+            .ok_or_else(|| {
+              syn::Error::new_spanned(
+                anchor,
+                format!("`{}` does not have field `{}`", self.name, anchor),
+              )
+            })?;
+          match field.placement {
+            Some(Placement::After(_)) => idx + 1,
+            _ => idx,
+          }
+        }
+      };
+      members.insert(idx, Member::Extra(field));
+    }
+
+    members
+      .into_iter()
+      .enumerate()
+      .map(|(idx, member)| {
+        let name = match self.style {
+          darling::ast::Style::Tuple => {
+            if let Member::Field(
+              _,
+              Field {
+                rename: Some(rename),
+                ..
+              },
+            ) = member
+            {
+              return Err(syn::Error::new_spanned(
+                rename,
+                format!("rename is not supported by tuple struct `{}`", self.name),
+              ));
+            }
+            syn::Member::Unnamed(idx.into())
+          }
+          _ => match member.ident() {
+            Some(ident) => syn::Member::Named(ident),
+            None => {
+              return Err(syn::Error::new_spanned(
+                &self.name,
+                format!(
+                  "the tuple field at position {} must be renamed when `{}` has named fields",
+                  idx, self.name
+                ),
+              ))
+            }
+          },
+        };
+        Ok((name, member))
+      })
+      .collect()
+  }
+}
+
 pub(crate) fn generate_struct(
   name: &syn::Ident,
   opts: &StructOpts,
   final_generics: &FinalGenerics,
 ) -> syn::Result<proc_macro2::TokenStream> {
   let mut fields = Vec::new();
-  let mut extra_fields = Vec::new();
-  let mut extra_attributes = Vec::new();
-  let mut ctr = 0;
-  if let Some(extra) = &opts.extra {
-    extra_attributes = extra.attributes.attrs.clone();
-    for field in extra.fields.values() {
-      let name = field
-        .name
-        .clone()
-        .unwrap_or_else(|| format_ident!("{}", ctr));
-      ctr += usize::from(field.named);
-      let ty = &field.src_ty;
-      let attributes = field.attributes.attrs.iter().chain(extra_attributes.iter());
-      let vis = field.vis.as_ref().unwrap_or(&field.src_vis);
-
-      extra_fields.push(quote! {
+  let extra_attributes = opts
+    .extra
+    .as_ref()
+    .map(|extra| extra.attributes.attrs.as_slice())
+    .unwrap_or_default();
+  for (name, member) in opts.members()? {
+    let ty = member.ty();
+    let attributes = member
+      .docs()
+      .iter()
+      .chain(member.cfgs())
+      .chain(member.attributes())
+      .chain(extra_attributes.iter());
+    let vis = member.vis().unwrap_or_else(|| member.src_vis());
+    match name {
+      syn::Member::Named(name) => fields.push(quote! {
           #(#attributes)*
           #vis #name: #ty,
-      });
+      }),
+      syn::Member::Unnamed(_) => fields.push(quote! {
+          #(#attributes)*
+          #vis #ty,
+      }),
     }
   }
 
-  opts.fields.iter().for_each(|(src_name, field)| {
-    let name = field.rename.clone().unwrap_or_else(|| {
+  let struct_attrs = opts.docs.iter().chain(&opts.attributes.attrs);
+  let vis = &opts.vis;
+  let final_struct_generics = &final_generics.final_struct_generics;
+  let self_where_clause = &final_generics.where_clause;
+  let allow = opts.allow_renamed();
+
+  Ok(match opts.style {
+    darling::ast::Style::Struct => quote! {
+      #(#struct_attrs)*
+      #allow
+      #vis struct #name #final_struct_generics #self_where_clause {
+          #(#fields)*
+      }
+    },
+    darling::ast::Style::Tuple => quote! {
+      #(#struct_attrs)*
+      #vis struct #name #final_struct_generics (
+          #(#fields)*
+      ) #self_where_clause;
+    },
+    darling::ast::Style::Unit => quote! {
+      #(#struct_attrs)*
+      #vis struct #name #final_struct_generics #self_where_clause;
+    },
+  })
+}
+
+pub(crate) fn generate_enum(
+  name: &syn::Ident,
+  opts: &StructOpts,
+  variants: &[Variant],
+  final_generics: &FinalGenerics,
+) -> syn::Result<proc_macro2::TokenStream> {
+  let variants = variants.iter().filter(|v| !v.skip).map(|v| {
+    let variant_name = v.name();
+    let attributes = v.docs.iter().chain(&v.attributes.attrs);
+    let discriminant = v.discriminant.as_ref().map(|d| quote!(= #d));
+    let fields = v.fields.iter().filter_map(|(src_name, field)| {
+      if field.skip.is_some() {
+        return None;
+      }
+      let ty = field.typ.as_ref().unwrap_or(&field.src_ty);
+      let attributes = field
+        .docs
+        .iter()
+        .chain(&field.cfgs)
+        .chain(&field.attributes.attrs);
       if field.named {
-        format_ident!("{}", src_name)
+        let name = field
+          .rename
+          .clone()
+          .unwrap_or_else(|| format_ident!("{}", src_name));
+        Some(quote!(#(#attributes)* #name: #ty))
       } else {
-        format_ident!("{}", ctr)
+        Some(quote!(#(#attributes)* #ty))
       }
     });
-    ctr += usize::from(field.named);
-    let ty = field.typ.as_ref().unwrap_or(&field.src_ty);
-    let skip = &field.skip;
-    let attributes = field.attributes.attrs.iter().chain(extra_attributes.iter());
-    let vis = field.vis.as_ref().unwrap_or(&field.src_vis);
-    if skip.is_none() {
-      fields.push(quote! {
-          #(#attributes)*
-          #vis #name: #ty,
-      });
+    match v.style {
+      darling::ast::Style::Struct => quote! {
+        #(#attributes)*
+        #variant_name { #(#fields,)* } #discriminant,
+      },
+      darling::ast::Style::Tuple => quote! {
+        #(#attributes)*
+        #variant_name(#(#fields,)*) #discriminant,
+      },
+      darling::ast::Style::Unit => quote! {
+        #(#attributes)*
+        #variant_name #discriminant,
+      },
     }
   });
 
-  let struct_attrs = &opts.attributes.attrs;
+  let enum_attrs = opts.docs.iter().chain(&opts.attributes.attrs);
   let vis = &opts.vis;
   let final_struct_generics = &final_generics.final_struct_generics;
   let self_where_clause = &final_generics.where_clause;
+  let allow = opts.allow_renamed();
 
   Ok(quote! {
-     #(#struct_attrs)*
-      #vis struct #name #final_struct_generics #self_where_clause {
-          #(#extra_fields)*
-          #(#fields)*
-      }
+    #(#enum_attrs)*
+    #allow
+    #vis enum #name #final_struct_generics #self_where_clause {
+      #(#variants)*
+    }
   })
 }
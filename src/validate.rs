@@ -0,0 +1,286 @@
+use super::*;
+
+/// A bound of `range` or `len`, which is a literal, or an expression in a string
+/// literal, e.g. `min = 1` or `min = "-1"`.
+pub(crate) struct Limit(syn::Expr);
+
+impl FromMeta for Limit {
+  fn from_value(value: &syn::Lit) -> darling::Result<Self> {
+    match value {
+      syn::Lit::Str(s) => syn::Expr::from_string(&s.value()).map(Self),
+      lit => Ok(Self(syn::Expr::Lit(syn::ExprLit {
+        attrs: Vec::new(),
+        lit: lit.clone(),
+      }))),
+    }
+  }
+}
+
+#[derive(FromMeta)]
+pub(crate) struct Limits {
+  min: Option<Limit>,
+  max: Option<Limit>,
+}
+
+impl Limits {
+  fn parse(name: &str, meta: &syn::Meta) -> darling::Result<Self> {
+    let limits = Self::from_meta(meta).map_err(|e| e.at(name))?;
+    if limits.min.is_none() && limits.max.is_none() {
+      return Err(
+        darling::Error::custom(format!("{} requires at least one of min and max", name))
+          .with_span(meta),
+      );
+    }
+    Ok(limits)
+  }
+
+  /// Returns the description of the limits, e.g. `min = 1, max = 10`.
+  fn describe(&self) -> String {
+    [("min", &self.min), ("max", &self.max)]
+      .into_iter()
+      .filter_map(|(name, limit)| {
+        limit
+          .as_ref()
+          .map(|Limit(expr)| format!("{} = {}", name, expr.to_token_stream()))
+      })
+      .collect::<Vec<_>>()
+      .join(", ")
+  }
+}
+
+pub(crate) enum Rule {
+  Range(Limits),
+  Len(Limits),
+  NonEmpty,
+  Custom(syn::Path),
+}
+
+impl Rule {
+  /// Returns the name of the rule in the errors, e.g. `range(min = 1, max = 10)`.
+  fn describe(&self) -> String {
+    match self {
+      Self::Range(limits) => format!("range({})", limits.describe()),
+      Self::Len(limits) => format!("len({})", limits.describe()),
+      Self::NonEmpty => "non_empty".to_string(),
+      Self::Custom(path) => format!("custom = {}", path.to_token_stream()),
+    }
+  }
+
+  /// Returns the condition of a valid `value`, which is a reference.
+  fn condition(&self, value: &proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    let limits = |limits: &Limits, actual: proc_macro2::TokenStream| {
+      let min = limits
+        .min
+        .as_ref()
+        .map(|Limit(min)| quote!(#actual >= #min));
+      let max = limits
+        .max
+        .as_ref()
+        .map(|Limit(max)| quote!(#actual <= #max));
+      let conditions = min.into_iter().chain(max);
+      quote!(#(#conditions)&&*)
+    };
+    match self {
+      Self::Range(l) => limits(l, quote!(*#value)),
+      Self::Len(l) => limits(l, quote!(#value.len())),
+      Self::NonEmpty => quote!(!#value.is_empty()),
+      Self::Custom(path) => quote!(#path(#value)),
+    }
+  }
+}
+
+/// The `validate` rules of a field.
+pub(crate) struct Validate {
+  rules: Vec<Rule>,
+}
+
+impl FromMeta for Validate {
+  fn from_list(items: &[syn::NestedMeta]) -> darling::Result<Self> {
+    let mut rules = Vec::with_capacity(items.len());
+    for item in items {
+      match item {
+        syn::NestedMeta::Meta(inner) => {
+          let name = darling::util::path_to_string(inner.path());
+          let rule = match name.as_str() {
+            "range" => Rule::Range(Limits::parse(&name, inner)?),
+            "len" => Rule::Len(Limits::parse(&name, inner)?),
+            "non_empty" => match inner {
+              syn::Meta::Path(_) => Rule::NonEmpty,
+              _ => return Err(darling::Error::unsupported_format("non-word").with_span(inner)),
+            },
+            "custom" => Rule::Custom(syn::Path::from_meta(inner).map_err(|e| e.at(&name))?),
+            other => {
+              return Err(
+                darling::Error::unknown_field_with_alts(
+                  other,
+                  &["range", "len", "non_empty", "custom"],
+                )
+                .with_span(inner),
+              );
+            }
+          };
+          rules.push(rule);
+        }
+        syn::NestedMeta::Lit(inner) => {
+          return Err(darling::Error::unsupported_format("literal").with_span(inner));
+        }
+      }
+    }
+    Ok(Self { rules })
+  }
+}
+
+impl Validate {
+  /// Returns the check of the given `value`, which is a reference, as a
+  /// `Result<(), error>` expression, `field` is the name in the error.
+  pub(crate) fn check(
+    &self,
+    error: &syn::Ident,
+    field: &str,
+    value: proc_macro2::TokenStream,
+  ) -> proc_macro2::TokenStream {
+    let conditions = self.rules.iter().map(|rule| rule.condition(&quote!(v)));
+    let rules = self.rules.iter().map(Rule::describe);
+    quote! {
+      {
+        let v = #value;
+        #(
+          if !(#conditions) {
+            ::core::result::Result::Err(#error::new(#field, #rules))
+          } else
+        )* {
+          ::core::result::Result::Ok(())
+        }
+      }
+    }
+  }
+}
+
+/// Returns the name of the generated validation error of the target.
+pub(crate) fn validation_error_name(name: &syn::Ident) -> syn::Ident {
+  format_ident!("{}ValidationError", name)
+}
+
+/// Returns the given conversion of a field of type `ty` followed by the check of its value,
+/// which is reported in the same way as the conversion.
+pub(crate) fn validated(
+  report: &ErrorReport,
+  ty: &syn::Type,
+  check: impl FnOnce(proc_macro2::TokenStream) -> proc_macro2::TokenStream,
+  converted: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+  match report {
+    ErrorReport::Collect(_) => {
+      let check = report.raise(check(quote!(converted)));
+      quote! {
+        {
+          let converted: ::core::option::Option<#ty> = #converted;
+          if let ::core::option::Option::Some(converted) = &converted {
+            let _ = #check;
+          }
+          converted
+        }
+      }
+    }
+    _ => {
+      let check = report.raise(check(quote!(&converted)));
+      quote! {
+        {
+          let converted: #ty = #converted;
+          #check;
+          converted
+        }
+      }
+    }
+  }
+}
+
+/// Generates the validation error and the `validate` method of the target, if any
+/// of its fields has `validate`.
+pub(crate) fn generate_validation(
+  opts: &StructOpts,
+  final_generics: &FinalGenerics,
+) -> syn::Result<proc_macro2::TokenStream> {
+  let members = opts.members()?;
+  let validated = members
+    .iter()
+    .filter_map(|(member, m)| match m {
+      Member::Field(_, field) => field.validate.as_ref().map(|v| (member, &field.cfgs, v)),
+      Member::Extra(_) => None,
+    })
+    .collect::<Vec<_>>();
+  if validated.is_empty() {
+    return Ok(quote!());
+  }
+
+  let name = &opts.name;
+  let vis = &opts.vis;
+  let error = validation_error_name(name);
+  let doc = format!(
+    "The error of a value of [`{}`] which fails a `validate` rule.",
+    name
+  );
+  let checks = validated.iter().map(|(member, cfgs, validate)| {
+    let check = validate.check(&error, &member_name(member), quote!(&self.#member));
+    quote!(#(#cfgs)* #check?;)
+  });
+  let impl_generics = &final_generics.impl_generics;
+  let ty_generics = &final_generics.ty_generics;
+  let where_clause = &final_generics.where_clause;
+  Ok(quote! {
+    #[doc = #doc]
+    #[derive(Debug)]
+    #vis struct #error {
+      field: &'static str,
+      rule: &'static str,
+      source: ::core::option::Option<::std::boxed::Box<dyn ::std::error::Error + ::core::marker::Send + ::core::marker::Sync + 'static>>,
+    }
+
+    impl #error {
+      fn new(field: &'static str, rule: &'static str) -> Self {
+        Self {
+          field,
+          rule,
+          source: ::core::option::Option::None,
+        }
+      }
+
+      /// Returns the name of the invalid field.
+      #vis fn field(&self) -> &'static str {
+        self.field
+      }
+
+      /// Returns the failed rule, e.g. `range(min = 1, max = 10)`, or `try_into` if
+      /// the value fails to convert in a setter.
+      #vis fn rule(&self) -> &'static str {
+        self.rule
+      }
+    }
+
+    impl ::core::fmt::Display for #error {
+      fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+        match &self.source {
+          ::core::option::Option::Some(source) => ::core::write!(f, "`{}` fails to convert: {}", self.field, source),
+          ::core::option::Option::None => ::core::write!(f, "`{}` violates `{}`", self.field, self.rule),
+        }
+      }
+    }
+
+    impl ::std::error::Error for #error {
+      fn source(&self) -> ::core::option::Option<&(dyn ::std::error::Error + 'static)> {
+        match &self.source {
+          ::core::option::Option::Some(source) => ::core::option::Option::Some(&**source),
+          ::core::option::Option::None => ::core::option::Option::None,
+        }
+      }
+    }
+
+    impl #impl_generics #name #ty_generics #where_clause {
+      /// Checks the `validate` rules of the fields, returns the first violation.
+      #vis fn validate(&self) -> ::core::result::Result<(), #error> {
+        #(#checks)*
+        ::core::result::Result::Ok(())
+      }
+    }
+  })
+}
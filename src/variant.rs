@@ -0,0 +1,159 @@
+use super::*;
+
+#[derive(Default, FromMeta)]
+struct VariantLevel {
+  parent: Option<syn::Ident>,
+  rename: Option<syn::Ident>,
+  #[darling(default)]
+  skip: bool,
+  #[darling(default)]
+  attributes: Attributes,
+}
+
+pub(crate) struct Variant {
+  pub(crate) src_name: syn::Ident,
+  pub(crate) rename: Option<syn::Ident>,
+  pub(crate) skip: bool,
+  pub(crate) attributes: Attributes,
+  /// The doc comments of the source variant.
+  pub(crate) docs: Vec<syn::Attribute>,
+  pub(crate) discriminant: Option<syn::Expr>,
+  pub(crate) style: darling::ast::Style,
+  pub(crate) fields: Vec<(String, Field)>,
+}
+
+impl Variant {
+  fn new(variant: &syn::Variant) -> Self {
+    Self {
+      src_name: variant.ident.clone(),
+      rename: None,
+      skip: false,
+      attributes: Attributes::default(),
+      docs: attrs_named(&variant.attrs, "doc"),
+      discriminant: variant.discriminant.as_ref().map(|(_, d)| d.clone()),
+      style: (&variant.fields).into(),
+      fields: Vec::new(),
+    }
+  }
+
+  /// Returns the name of the variant in the target enum.
+  pub(crate) fn name(&self) -> &syn::Ident {
+    self.rename.as_ref().unwrap_or(&self.src_name)
+  }
+}
+
+/// Collects the variants of the source enum into every target.
+pub(crate) fn collect_variants(
+  data: &syn::DataEnum,
+  targets: &mut [StructOpts],
+) -> darling::Result<()> {
+  for opts in targets.iter_mut() {
+    opts.variants = Some(Vec::with_capacity(data.variants.len()));
+  }
+
+  let num_targets = targets.len();
+  for variant in data.variants.iter() {
+    let mut variants: Vec<(String, Variant)> = targets
+      .iter()
+      .map(|opts| (opts.name.to_string(), Variant::new(variant)))
+      .collect();
+
+    for attr in &variant.attrs {
+      if !attr.path.is_ident("fromit") {
+        continue;
+      }
+
+      let data = darling::util::parse_attribute_to_meta_list(attr)?;
+      if data.nested.is_empty() {
+        continue;
+      }
+      let opts = VariantLevel::from_list(&data.nested.into_iter().collect::<Vec<_>>())?;
+      let v = match (num_targets, &opts.parent) {
+        (0, _) => continue,
+        (1, None) => &mut variants[0].1,
+        (_, None) => {
+          return Err(
+            darling::Error::custom(
+              "parent must be specified when there are more than one enum needed to be generated",
+            )
+            .with_span(attr),
+          )
+        }
+        (_, Some(parent)) => match variants.iter_mut().find(|(name, _)| parent == name) {
+          Some((_, v)) => v,
+          None => {
+            return Err(
+              darling::Error::custom(format!("Does not have parent {}", parent)).with_span(parent),
+            )
+          }
+        },
+      };
+
+      if opts.rename.is_some() {
+        v.rename = opts.rename;
+      }
+      v.skip |= opts.skip;
+      v.attributes.attrs.extend(opts.attributes.attrs);
+    }
+
+    collect_fields(
+      &variant.fields,
+      variants
+        .iter_mut()
+        .map(|(parent, v)| (parent.as_str(), &mut v.fields))
+        .collect(),
+    )?;
+
+    for (opts, (_, v)) in targets.iter_mut().zip(variants) {
+      for (_, f) in v.fields.iter() {
+        if f.vis.is_some() {
+          return Err(
+            darling::Error::custom("vis is not supported for enum variant fields")
+              .with_span(&variant.ident),
+          );
+        }
+        let getter = &f.getter;
+        if getter.rename.is_some()
+          || getter.style.is_some()
+          || getter.ignore
+          || getter.vis.is_some()
+          || getter.result.is_some()
+        {
+          return Err(
+            darling::Error::custom("getter is not supported for enum variant fields")
+              .with_span(&variant.ident),
+          );
+        }
+        let setter = &f.setter;
+        if setter.rename.is_some()
+          || setter.style.is_some()
+          || setter.ignore
+          || setter.vis.is_some()
+          || setter.bound.bound.is_some()
+        {
+          return Err(
+            darling::Error::custom("setter is not supported for enum variant fields")
+              .with_span(&variant.ident),
+          );
+        }
+        let computed = [&f.from, &f.into]
+          .into_iter()
+          .any(|c| matches!(c, Some(c) if c.expr.is_some()));
+        if computed {
+          return Err(
+            darling::Error::custom("expr is not supported for enum variant fields")
+              .with_span(&variant.ident),
+          );
+        }
+        if f.validate.is_some() {
+          return Err(
+            darling::Error::custom("validate is not supported for enum variant fields")
+              .with_span(&variant.ident),
+          );
+        }
+      }
+      opts.variants.as_mut().unwrap().push(v);
+    }
+  }
+  Ok(())
+}
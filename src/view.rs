@@ -0,0 +1,369 @@
+use super::*;
+
+/// `view` generates a target borrowing the fields of the source, `view(mut)`
+/// borrows them mutably, and `view(mut, split)` adds the target to `split_mut`.
+#[derive(Default)]
+pub(crate) struct View {
+  pub(crate) mutable: bool,
+  pub(crate) split: bool,
+}
+
+impl FromMeta for View {
+  fn from_word() -> darling::Result<Self> {
+    Ok(Self::default())
+  }
+
+  fn from_list(items: &[syn::NestedMeta]) -> darling::Result<Self> {
+    let mut view = Self::default();
+    for item in items {
+      match item {
+        syn::NestedMeta::Meta(syn::Meta::Path(path)) if path.is_ident("mut") => view.mutable = true,
+        syn::NestedMeta::Meta(syn::Meta::Path(path)) if path.is_ident("split") => view.split = true,
+        syn::NestedMeta::Meta(inner) => {
+          return Err(
+            darling::Error::unknown_field_with_alts(
+              &darling::util::path_to_string(inner.path()),
+              &["mut", "split"],
+            )
+            .with_span(inner),
+          );
+        }
+        syn::NestedMeta::Lit(inner) => {
+          return Err(darling::Error::unsupported_format("literal").with_span(inner));
+        }
+      }
+    }
+    if view.split && !view.mutable {
+      return Err(darling::Error::custom(
+        "split is only supported by view(mut)",
+      ));
+    }
+    Ok(view)
+  }
+}
+
+impl View {
+  /// Returns the reference to the source, `&'a` or `&'a mut`.
+  pub(crate) fn source_ref(&self, src_generics: &syn::Generics) -> proc_macro2::TokenStream {
+    let lifetime = view_lifetime(src_generics);
+    match self.mutable {
+      true => quote!(&#lifetime mut),
+      false => quote!(&#lifetime),
+    }
+  }
+}
+
+/// Returns the lifetime of the borrowed source of a view, `'a`, or `'a1`, `'a2`, ...
+/// if the source already has the lifetime.
+pub(crate) fn view_lifetime(src_generics: &syn::Generics) -> syn::Lifetime {
+  fresh_lifetime(src_generics, "a")
+}
+
+/// Returns the type of a view field borrowing a source field of type `ty`, `&'a T`,
+/// `&'a str` for `String` or `&'a [T]` for `Vec<T>`, or `&'a mut T` if `mutable`.
+fn view_ty(ty: &syn::Type, lifetime: &syn::Lifetime, mutable: bool) -> syn::Type {
+  if mutable {
+    return syn::parse_quote!(&#lifetime mut #ty);
+  }
+  if let syn::Type::Path(p) = ty {
+    if let (None, Some(last)) = (&p.qself, p.path.segments.last()) {
+      match (last.ident.to_string().as_str(), &last.arguments) {
+        ("String", syn::PathArguments::None) => return syn::parse_quote!(&#lifetime str),
+        ("Vec", syn::PathArguments::AngleBracketed(args)) if args.args.len() == 1 => {
+          let elem = &args.args[0];
+          return syn::parse_quote!(&#lifetime [#elem]);
+        }
+        _ => {}
+      }
+    }
+  }
+  syn::parse_quote!(&#lifetime #ty)
+}
+
+impl StructOpts {
+  /// Turns the inherited fields of a view into borrows of the source fields, which
+  /// keep their `type` and `from` if specified.
+  pub(crate) fn borrow_fields(&mut self, src_generics: &syn::Generics) {
+    let Some(view) = &self.view else {
+      return;
+    };
+    let lifetime = view_lifetime(src_generics);
+    for (key, field) in self.fields.iter_mut() {
+      if field.typ.is_none() {
+        field.typ = Some(view_ty(&field.src_ty, &lifetime, view.mutable));
+      }
+      if field.from.is_none() {
+        let member = src_member(key, field.named);
+        let expr = match view.mutable {
+          true => syn::parse_quote!(&mut s.#member),
+          false => syn::parse_quote!(&s.#member),
+        };
+        field.from = Some(FieldConverter {
+          expr: Some(expr),
+          ..Default::default()
+        });
+      }
+    }
+  }
+}
+
+/// Prepends the lifetime of a view to the generics of the target.
+pub(crate) fn view_generics(generics: FinalGenerics) -> syn::Result<FinalGenerics> {
+  let lifetime = view_lifetime(&generics.src_generics);
+  let prepend = |mut g: syn::Generics| {
+    g.params.insert(
+      0,
+      syn::GenericParam::Lifetime(syn::LifetimeDef::new(lifetime.clone())),
+    );
+    g
+  };
+  // the source type keeps its substitutions
+  let src_ty_generics = generics.src_ty_generics;
+  let mut generics = FinalGenerics::new(
+    &generics.src_generics,
+    prepend(generics.generics),
+    prepend(generics.final_generics),
+  );
+  generics.src_ty_generics = src_ty_generics;
+  Ok(generics)
+}
+
+/// Checks the targets of `view(mut, split)`, which must borrow disjoint fields.
+pub(crate) fn check_split(targets: &[StructOpts]) -> darling::Result<()> {
+  let split = targets
+    .iter()
+    .filter(|opts| matches!(&opts.view, Some(view) if view.split))
+    .collect::<Vec<_>>();
+  if split.len() == 1 {
+    return Err(
+      darling::Error::custom("split_mut needs at least two targets with view(mut, split)")
+        .with_span(&split[0].name),
+    );
+  }
+  for (idx, opts) in split.iter().enumerate() {
+    for other in &split[..idx] {
+      let overlap = opts.fields.iter().find(|(key, field)| {
+        field.skip.is_none()
+          && other
+            .fields
+            .iter()
+            .any(|(other_key, other_field)| other_key == key && other_field.skip.is_none())
+      });
+      if let Some((key, _)) = overlap {
+        return Err(
+          darling::Error::custom(format!(
+            "`{}` and `{}` both borrow field `{}`, split_mut needs disjoint fields",
+            other.name, opts.name, key
+          ))
+          .with_span(&opts.name),
+        );
+      }
+    }
+  }
+  Ok(())
+}
+
+/// Generates `split_mut` on the source, which borrows the disjoint targets of
+/// `view(mut, split)` at once, `parts` are their types and values built from `s`.
+pub(crate) fn generate_split_mut(
+  src_name: &syn::Ident,
+  src_generics: &syn::Generics,
+  vis: &syn::Visibility,
+  parts: &[(proc_macro2::TokenStream, proc_macro2::TokenStream)],
+) -> proc_macro2::TokenStream {
+  if parts.is_empty() {
+    return quote!();
+  }
+  let lifetime = view_lifetime(src_generics);
+  let (impl_generics, ty_generics, where_clause) = src_generics.split_for_impl();
+  let tys = parts.iter().map(|(ty, _)| ty);
+  let values = parts.iter().map(|(_, value)| value);
+  quote! {
+    impl #impl_generics #src_name #ty_generics #where_clause {
+      /// Borrows the disjoint fields of the split views at once.
+      #vis fn split_mut<#lifetime>(&#lifetime mut self) -> (#(#tys,)*) {
+        let s = self;
+        (#(#values,)*)
+      }
+    }
+  }
+}
+
+/// Returns the name of the lazy view of the target, e.g. `FooDbLazy`.
+fn lazy_view_name(name: &syn::Ident) -> syn::Ident {
+  format_ident!("{}Lazy", name)
+}
+
+/// Generates the lazy view of the target if `lazy_view`, which wraps a borrowed
+/// source and converts a field on each call of its getter.
+pub(crate) fn generate_lazy_view(
+  src_name: &syn::Ident,
+  src_generics: &syn::Generics,
+  opts: &StructOpts,
+) -> syn::Result<proc_macro2::TokenStream> {
+  if !opts.lazy_view {
+    return Ok(quote!());
+  }
+  let (try_, style) = match (&opts.converter.try_from, &opts.converter.from) {
+    (Some(try_), _) => (true, try_.style),
+    (None, Some(from)) if from.context.is_some() => {
+      return Err(syn::Error::new_spanned(
+        &opts.name,
+        "lazy_view is not supported with context",
+      ))
+    }
+    (None, Some(from)) => (false, from.style),
+    (None, None) => (false, Style::Ref),
+  };
+  if !try_
+    && opts
+      .fields
+      .iter()
+      .any(|(_, f)| f.skip.is_none() && f.validate.is_some())
+  {
+    return Err(syn::Error::new_spanned(
+      &opts.name,
+      "validate is only checked by the lazy_view of a try_from target",
+    ));
+  }
+  let report = ErrorReport::Boxed;
+  let validation_error = validation_error_name(&opts.name);
+  let boxed = quote!(
+    ::std::boxed::Box<
+      dyn ::std::error::Error + ::core::marker::Send + ::core::marker::Sync + 'static,
+    >
+  );
+  let lifetime = view_lifetime(src_generics);
+
+  let mut getters = Vec::new();
+  for (field_name, member) in opts.members()? {
+    let getter = member.getter();
+    let Some(fn_name) = getter_name(opts, &field_name, getter) else {
+      continue;
+    };
+    let vis = getter.vis.as_ref().unwrap_or_else(|| {
+      opts
+        .getters
+        .vis_all
+        .as_ref()
+        .unwrap_or_else(|| member.vis().unwrap_or(&opts.vis))
+    });
+    let ty = member.ty();
+    let attrs = member.docs().iter().chain(member.cfgs());
+    let attrs = quote!(#(#attrs)*);
+    let (ty, value) = match member {
+      Member::Extra(field) if field.from.is_none() => continue,
+      Member::Extra(field) => (
+        quote!(#ty),
+        field.convert_from(try_.then_some(&report), Style::Ref, false),
+      ),
+      Member::Field(key, field) if field.typ.is_none() && field.from.is_none() => {
+        let member = src_member(key, field.named);
+        // a validated field is checked before it is borrowed
+        if let Some(validate) = &field.validate {
+          let key = member_name(&field_name);
+          (
+            quote!(&#lifetime #ty),
+            validated(
+              &report,
+              &syn::parse_quote!(&#lifetime #ty),
+              |value| validate.check(&validation_error, &key, quote!(*#value)),
+              quote!(&s.#member),
+            ),
+          )
+        } else {
+          getters.push(quote! {
+            #attrs
+            #[inline]
+            #vis fn #fn_name(&self) -> &#lifetime #ty {
+              &self.0.#member
+            }
+          });
+          continue;
+        }
+      }
+      Member::Field(key, field) => {
+        // a moved field is cloned out of the borrowed source
+        let member = src_member(key, field.named);
+        let final_style = field
+          .from
+          .as_ref()
+          .and_then(|from| from.style)
+          .unwrap_or(style);
+        let value = match final_style {
+          Style::Ref => quote!(&s.#member),
+          Style::Move => quote!(::core::clone::Clone::clone(&s.#member)),
+        };
+        let value = field.convert_from(try_.then_some(&report), final_style, false, value)?;
+        let value = match &field.validate {
+          Some(validate) => {
+            let key = member_name(&field_name);
+            validated(
+              &report,
+              field.target_ty(),
+              |value| validate.check(&validation_error, &key, value),
+              value,
+            )
+          }
+          None => value,
+        };
+        (quote!(#ty), value)
+      }
+    };
+    getters.push(if try_ {
+      quote! {
+        #attrs
+        #vis fn #fn_name(&self) -> ::core::result::Result<#ty, #boxed> {
+          let s = self.0;
+          ::core::result::Result::Ok(#value)
+        }
+      }
+    } else {
+      quote! {
+        #attrs
+        #vis fn #fn_name(&self) -> #ty {
+          let s = self.0;
+          #value
+        }
+      }
+    });
+  }
+
+  let name = &opts.name;
+  let lazy = lazy_view_name(name);
+  let vis = &opts.vis;
+  let doc = format!(
+    "A view of [`{}`] as [`{}`], which converts a field on each call of its getter.",
+    src_name, name
+  );
+  let mut generics = src_generics.clone();
+  generics.params.insert(
+    0,
+    syn::GenericParam::Lifetime(syn::LifetimeDef::new(lifetime.clone())),
+  );
+  let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+  let (_, src_ty_generics, _) = src_generics.split_for_impl();
+  let allow = opts.allow_renamed();
+  Ok(quote! {
+    #[doc = #doc]
+    #[derive(::core::clone::Clone, ::core::marker::Copy)]
+    #vis struct #lazy #impl_generics (&#lifetime #src_name #src_ty_generics) #where_clause;
+
+    impl #impl_generics ::core::convert::From<&#lifetime #src_name #src_ty_generics> for #lazy #ty_generics #where_clause {
+      fn from(s: &#lifetime #src_name #src_ty_generics) -> Self {
+        Self(s)
+      }
+    }
+
+    #allow
+    impl #impl_generics #lazy #ty_generics #where_clause {
+      /// Returns the borrowed source.
+      #[inline]
+      #vis fn source(&self) -> &#lifetime #src_name #src_ty_generics {
+        self.0
+      }
+
+      #(#getters)*
+    }
+  })
+}